@@ -0,0 +1,89 @@
+use crate::Edge;
+
+/// Combinatorial embedding of a graph, given as a rotation system.
+///
+/// Each vertex owns the cyclic order of its incident edges, identified by
+/// their index into the edge slice the embedding was computed from. All
+/// rotations share one orientation, so faces can be traced with
+/// [`Embedding::faces`]. A self-loop occurs twice, consecutively, in the
+/// rotation of its vertex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embedding {
+    edges: Vec<Edge>,
+    offsets: Vec<usize>,
+    rotation: Vec<usize>,
+}
+
+impl Embedding {
+    pub(crate) fn from_raw(edges: &[Edge], offsets: Vec<usize>, rotation: Vec<usize>) -> Self {
+        debug_assert_eq!(offsets.last().copied(), Some(rotation.len()));
+        Self {
+            edges: edges.to_vec(),
+            offsets,
+            rotation,
+        }
+    }
+
+    pub fn num_verts(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// The edges the indices in every rotation refer to.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Cyclic order of the edge indices incident to `v`.
+    pub fn rotation(&self, v: usize) -> &[usize] {
+        &self.rotation[self.offsets[v]..self.offsets[v + 1]]
+    }
+
+    /// Trace every face as the cyclic sequence of edge indices along its
+    /// boundary walk. A disconnected graph yields one walk per face of each
+    /// connected component; isolated vertices contribute nothing.
+    pub fn faces(&self) -> Vec<Vec<usize>> {
+        let twin = self.twins();
+        let mut seen = vec![false; self.rotation.len()];
+        let mut out = Vec::new();
+        for start in 0..self.rotation.len() {
+            if seen[start] {
+                continue;
+            }
+            let mut face = Vec::new();
+            let mut dart = start;
+            while !seen[dart] {
+                seen[dart] = true;
+                face.push(self.rotation[dart]);
+                dart = self.cyclic_succ(twin[dart]);
+            }
+            out.push(face);
+        }
+        out
+    }
+
+    /// Pair every slot of the flattened rotation with the slot holding the
+    /// other end of the same edge.
+    fn twins(&self) -> Vec<usize> {
+        let mut first = vec![usize::MAX; self.edges.len()];
+        let mut twin = vec![usize::MAX; self.rotation.len()];
+        for (slot, &e) in self.rotation.iter().enumerate() {
+            match first[e] {
+                usize::MAX => first[e] = slot,
+                other => {
+                    twin[other] = slot;
+                    twin[slot] = other;
+                }
+            }
+        }
+        twin
+    }
+
+    fn cyclic_succ(&self, slot: usize) -> usize {
+        let v = self.offsets.partition_point(|&o| o <= slot) - 1;
+        if slot + 1 == self.offsets[v + 1] {
+            self.offsets[v]
+        } else {
+            slot + 1
+        }
+    }
+}
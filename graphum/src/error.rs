@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::Edge;
+
+/// Why a `graphum` entry point refused a call: either its input was invalid
+/// (an id or vertex out of range, a removed candidate, a mask of the wrong
+/// length), or the current state does not allow it (a candidate that is not
+/// selected, required or forbidden, a selection that would stop being
+/// planar, or a transaction that is, or is not, open). Nothing is changed
+/// when an error is returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// Edge `edge` has endpoint `vertex`, which is not below `num_verts`.
+    VertexOutOfRange {
+        edge: usize,
+        vertex: usize,
+        num_verts: usize,
+    },
+    /// A per-edge mask has `mask` entries for `edges` edges.
+    LengthMismatch { edges: usize, mask: usize },
+    /// Vertex `vertex` is not below `num_verts`.
+    NoSuchVertex { vertex: usize, num_verts: usize },
+    /// Edge id `edge` does not name one of the `num_edges` candidates.
+    EdgeOutOfRange { edge: usize, num_edges: usize },
+    /// Edge id `edge` named a candidate that has since been removed.
+    RemovedCandidate { edge: usize },
+    /// Vertex `vertex` has been removed.
+    RemovedVertex { vertex: usize },
+    /// Candidate `edge` had to be selected for the operation.
+    NotSelected { edge: usize },
+    /// Candidate `edge` is required, so it cannot be deselected or removed.
+    Required { edge: usize },
+    /// Candidate `edge` is forbidden, so it cannot be selected.
+    Forbidden { edge: usize },
+    /// Selecting candidate `edge` would make the selection non-planar.
+    NotAddable { edge: usize },
+    /// The selection a transaction would commit is not planar.
+    NotPlanar,
+    /// A witness search was asked for at most zero witnesses.
+    ZeroWitnessLimit,
+    /// A transaction is open, so `begin` or a change to the candidate set
+    /// was refused.
+    TransactionActive,
+    /// `commit` or `rollback` was called without an open transaction.
+    NoTransaction,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::VertexOutOfRange {
+                edge,
+                vertex,
+                num_verts,
+            } => write!(
+                f,
+                "edge {edge} has endpoint {vertex}, but the graph has {num_verts} vertices"
+            ),
+            Error::LengthMismatch { edges, mask } => {
+                write!(f, "mask has {mask} entries for {edges} edges")
+            }
+            Error::NoSuchVertex { vertex, num_verts } => {
+                write!(
+                    f,
+                    "vertex {vertex} is out of range for {num_verts} vertices"
+                )
+            }
+            Error::EdgeOutOfRange { edge, num_edges } => {
+                write!(f, "edge id {edge} is out of range for {num_edges} edges")
+            }
+            Error::RemovedCandidate { edge } => {
+                write!(f, "edge id {edge} names a removed candidate")
+            }
+            Error::RemovedVertex { vertex } => {
+                write!(f, "vertex {vertex} has been removed")
+            }
+            Error::NotSelected { edge } => write!(f, "edge id {edge} is not selected"),
+            Error::Required { edge } => write!(f, "edge id {edge} is required"),
+            Error::Forbidden { edge } => write!(f, "edge id {edge} is forbidden"),
+            Error::NotAddable { edge } => {
+                write!(f, "selecting edge id {edge} would break planarity")
+            }
+            Error::NotPlanar => write!(f, "the selection to commit is not planar"),
+            Error::ZeroWitnessLimit => {
+                write!(f, "max_witnesses must be at least 1, or None for no limit")
+            }
+            Error::TransactionActive => write!(f, "a transaction is already open"),
+            Error::NoTransaction => write!(f, "no transaction is open"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Check that every endpoint in `edges` is a vertex.
+pub(crate) fn check_edges(num_verts: usize, edges: &[Edge]) -> Result<(), Error> {
+    for (edge, &e) in edges.iter().enumerate() {
+        check_edge(num_verts, edge, e)?;
+    }
+    Ok(())
+}
+
+/// [`check_edges`] for a single edge, reported as edge `edge`.
+pub(crate) fn check_edge(num_verts: usize, edge: usize, e: Edge) -> Result<(), Error> {
+    for vertex in [e.u, e.v] {
+        if vertex >= num_verts {
+            return Err(Error::VertexOutOfRange {
+                edge,
+                vertex,
+                num_verts,
+            });
+        }
+    }
+    Ok(())
+}
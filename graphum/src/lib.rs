@@ -1,4 +1,8 @@
 pub use ogdf_sys::{Edge, autogen};
 
+pub mod embedding;
+mod error;
 pub mod mps;
 pub mod spqr;
+
+pub use error::Error;
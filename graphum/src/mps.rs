@@ -1,15 +1,308 @@
-use crate::{Edge, autogen::graph};
+use crate::{Edge, Error, autogen::graph, embedding::Embedding, error::check_edges};
 use cxx::{CxxVector, UniquePtr};
+use std::collections::HashMap;
 
 /// Run the Boyer–Myrvold planarity test.
 ///
+/// Self-loops are ignored; an endpoint outside `0..num_verts` is an error.
+///
 /// Returns:
 ///   * `None`  – the graph is planar.
 ///   * `Some(edges)` – a Kuratowski witness subgraph when the graph is non-planar.
 ///     The edges are returned as a list of (u, v) with 0-based vertex indices.
 ///     If OGDF returns an empty vector for some degenerate non-planar case,
 ///     this is surfaced as `Some(Vec::new())`.
-pub fn boyer_myrvold_witness(num_verts: usize, edges: &[Edge]) -> Option<Vec<Edge>> {
+pub fn boyer_myrvold_witness(num_verts: usize, edges: &[Edge]) -> Result<Option<Vec<Edge>>, Error> {
+    check_edges(num_verts, edges)?;
+    let edge_buf = edge_vec(edges);
+
+    let witness: UniquePtr<CxxVector<graph::Edge>> =
+        unsafe { graph::boyer_myrvold_witness(num_verts, edge_buf.as_ref().unwrap()) };
+
+    let out: Vec<Edge> = witness.iter().map(|e| Edge { u: e.u, v: e.v }).collect();
+    Ok((!out.is_empty()).then_some(out))
+}
+
+/// Run the Boyer–Myrvold planarity test, reporting the witness by edge index.
+///
+/// Returns `None` if the graph is planar, and otherwise the sorted indices
+/// into `edges` of a Kuratowski subgraph. Among parallel input edges, the
+/// witness always uses the one with the smallest index.
+pub fn boyer_myrvold_witness_ids(
+    num_verts: usize,
+    edges: &[Edge],
+) -> Result<Option<Vec<usize>>, Error> {
+    check_edges(num_verts, edges)?;
+    let edge_buf = edge_vec(edges);
+
+    let witness: UniquePtr<CxxVector<usize>> =
+        unsafe { graph::boyer_myrvold_witness_ids(num_verts, edge_buf.as_ref().unwrap()) };
+
+    let out: Vec<usize> = witness.iter().copied().collect();
+    Ok((!out.is_empty()).then_some(out))
+}
+
+/// Kuratowski subgraph certifying that a graph is non-planar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KuratowskiWitness {
+    /// Witness edges as normalized (u, v) pairs with `u < v`, sorted and
+    /// deduplicated.
+    pub edges: Vec<Edge>,
+    /// Sorted indices of the witness edges into the input edge slice.
+    pub edge_ids: Vec<usize>,
+}
+
+/// The Kuratowski graph a witness subdivides.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KuratowskiKind {
+    K5,
+    K33,
+}
+
+/// A Kuratowski witness resolved into its subdivision structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KuratowskiSubdivision {
+    pub kind: KuratowskiKind,
+    /// The 5 (K5) or 6 (K3,3) branch vertices. They are sorted, except that
+    /// for K3,3 the first three form the side containing the smallest one.
+    pub branch_vertices: Vec<usize>,
+    /// One path per edge of the Kuratowski graph, as the ordered vertex chain
+    /// from the smaller branch vertex to the larger. Sorted by endpoints.
+    pub paths: Vec<Vec<usize>>,
+}
+
+impl KuratowskiWitness {
+    fn from_ids(input: &[Edge], edge_ids: Vec<usize>) -> Self {
+        let mut edges: Vec<Edge> = edge_ids
+            .iter()
+            .map(|&i| {
+                let Edge { u, v } = input[i];
+                Edge {
+                    u: u.min(v),
+                    v: u.max(v),
+                }
+            })
+            .collect();
+        edges.sort_unstable_by_key(|e| (e.u, e.v));
+        edges.dedup();
+        Self { edges, edge_ids }
+    }
+
+    /// Resolve the witness into a K5 or K3,3 subdivision.
+    ///
+    /// Returns `None` if the edges do not form exactly one such subdivision,
+    /// which can happen for witnesses extracted with
+    /// [`BoyerMyrvoldOptions::bundles`] set.
+    pub fn subdivision(&self) -> Option<KuratowskiSubdivision> {
+        let mut adj: HashMap<usize, Vec<usize>> = HashMap::new();
+        for e in &self.edges {
+            adj.entry(e.u).or_default().push(e.v);
+            adj.entry(e.v).or_default().push(e.u);
+        }
+
+        let mut branch: Vec<usize> = adj
+            .iter()
+            .filter(|(_, nbrs)| nbrs.len() != 2)
+            .map(|(&v, _)| v)
+            .collect();
+        branch.sort_unstable();
+        let kind = match (branch.len(), adj[branch.first()?].len()) {
+            (5, 4) => KuratowskiKind::K5,
+            (6, 3) => KuratowskiKind::K33,
+            _ => return None,
+        };
+        let branch_deg = adj[&branch[0]].len();
+        if branch.iter().any(|b| adj[b].len() != branch_deg) {
+            return None;
+        }
+
+        let mut paths = Vec::new();
+        for &b in &branch {
+            for &first in &adj[&b] {
+                let mut path = vec![b, first];
+                while adj[path.last().unwrap()].len() == 2 {
+                    let [prev, cur] = [path[path.len() - 2], path[path.len() - 1]];
+                    let nbrs = &adj[&cur];
+                    path.push(if nbrs[0] == prev { nbrs[1] } else { nbrs[0] });
+                    if path.len() > self.edges.len() + 1 {
+                        return None;
+                    }
+                }
+                if b < *path.last().unwrap() {
+                    paths.push(path);
+                }
+            }
+        }
+        let path_edges: usize = paths.iter().map(|p| p.len() - 1).sum();
+        if path_edges != self.edges.len() {
+            return None;
+        }
+        paths.sort_unstable_by_key(|p| (p[0], p[p.len() - 1]));
+
+        let ends: Vec<(usize, usize)> = paths.iter().map(|p| (p[0], p[p.len() - 1])).collect();
+        let expected: Vec<(usize, usize)> = match kind {
+            KuratowskiKind::K5 => (0..5)
+                .flat_map(|i| ((i + 1)..5).map(move |j| (i, j)))
+                .map(|(i, j)| (branch[i], branch[j]))
+                .collect(),
+            KuratowskiKind::K33 => {
+                let (side, other): (Vec<usize>, Vec<usize>) = branch
+                    .iter()
+                    .partition(|&&b| b == branch[0] || !ends.contains(&(branch[0], b)));
+                if side.len() != 3 {
+                    return None;
+                }
+                branch = side.iter().chain(&other).copied().collect();
+                let mut expected: Vec<(usize, usize)> = side
+                    .iter()
+                    .flat_map(|&a| other.iter().map(move |&b| (a.min(b), a.max(b))))
+                    .collect();
+                expected.sort_unstable();
+                expected
+            }
+        };
+        if ends != expected {
+            return None;
+        }
+
+        Some(KuratowskiSubdivision {
+            kind,
+            branch_vertices: branch,
+            paths,
+        })
+    }
+}
+
+/// Run the Boyer–Myrvold planarity test and resolve the witness, if any,
+/// into its K5 or K3,3 subdivision.
+pub fn kuratowski_subdivision(
+    num_verts: usize,
+    edges: &[Edge],
+) -> Result<Option<KuratowskiSubdivision>, Error> {
+    let witness = boyer_myrvold_witness_ids(num_verts, edges)?;
+    Ok(witness.and_then(|ids| KuratowskiWitness::from_ids(edges, ids).subdivision()))
+}
+
+/// Knobs for Kuratowski witness extraction, mirroring the arguments of
+/// OGDF's `BoyerMyrvold::planarEmbedDestructive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoyerMyrvoldOptions {
+    /// Upper bound on the number of witnesses to extract; `None` extracts
+    /// every witness OGDF finds. `Some(0)` is rejected with
+    /// [`Error::ZeroWitnessLimit`].
+    pub max_witnesses: Option<usize>,
+    /// Extract bundles of Kuratowski paths instead of single paths, which
+    /// may yield witnesses that are unions of several subdivisions.
+    pub bundles: bool,
+    /// Seed for a randomized DFS tree; `None` uses the deterministic tree.
+    ///
+    /// The seed goes to OGDF's process-wide random source. Seeded calls are
+    /// serialized with each other, so the same seed gives the same witnesses
+    /// on any thread, but other code drawing from that source can still
+    /// interleave with them.
+    pub random_dfs_seed: Option<u32>,
+    /// Skip witnesses of the E2 minor type.
+    pub avoid_e2_minors: bool,
+}
+
+impl Default for BoyerMyrvoldOptions {
+    /// The settings used by [`boyer_myrvold_witness`].
+    fn default() -> Self {
+        Self {
+            max_witnesses: Some(1),
+            bundles: false,
+            random_dfs_seed: None,
+            avoid_e2_minors: true,
+        }
+    }
+}
+
+/// Run the Boyer–Myrvold planarity test and extract Kuratowski witnesses.
+///
+/// Returns an empty vector if the graph is planar. Otherwise returns the
+/// distinct witnesses OGDF extracted, in discovery order, each normalized
+/// like the witness of [`boyer_myrvold_witness`].
+pub fn kuratowski_witnesses(
+    num_verts: usize,
+    edges: &[Edge],
+    options: &BoyerMyrvoldOptions,
+) -> Result<Vec<KuratowskiWitness>, Error> {
+    check_edges(num_verts, edges)?;
+    if options.max_witnesses == Some(0) {
+        return Err(Error::ZeroWitnessLimit);
+    }
+    let edge_buf = edge_vec(edges);
+    let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+    let mut flat: UniquePtr<CxxVector<usize>> = CxxVector::new();
+
+    let planar = unsafe {
+        graph::kuratowski_witnesses(
+            num_verts,
+            edge_buf.as_ref().unwrap(),
+            options.max_witnesses.unwrap_or(0),
+            options.bundles,
+            options.random_dfs_seed.is_some(),
+            options.random_dfs_seed.unwrap_or(0),
+            options.avoid_e2_minors,
+            offsets.pin_mut(),
+            flat.pin_mut(),
+        )
+    };
+    if planar {
+        return Ok(Vec::new());
+    }
+
+    let flat = flat.as_slice();
+    let mut out: Vec<KuratowskiWitness> = Vec::new();
+    for w in offsets.as_slice().windows(2) {
+        let ids = flat[w[0]..w[1]].to_vec();
+        if !out.iter().any(|k| k.edge_ids == ids) {
+            out.push(KuratowskiWitness::from_ids(edges, ids));
+        }
+    }
+    Ok(out)
+}
+
+/// Run the Boyer–Myrvold planarity test and keep the embedding it computes.
+///
+/// Returns the rotation system of a planar embedding, keyed by indices into
+/// `edges`, or the Kuratowski witness OGDF extracted if the graph is
+/// non-planar. Parallel edges and self-loops are accepted and placed
+/// alongside the embedding of the underlying simple graph.
+pub fn planar_embedding(
+    num_verts: usize,
+    edges: &[Edge],
+) -> Result<Result<Embedding, KuratowskiWitness>, Error> {
+    check_edges(num_verts, edges)?;
+    let edge_buf = edge_vec(edges);
+    let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+    let mut rotation: UniquePtr<CxxVector<usize>> = CxxVector::new();
+    let mut witness: UniquePtr<CxxVector<usize>> = CxxVector::new();
+
+    let planar = unsafe {
+        graph::planar_embedding(
+            num_verts,
+            edge_buf.as_ref().unwrap(),
+            offsets.pin_mut(),
+            rotation.pin_mut(),
+            witness.pin_mut(),
+        )
+    };
+
+    if !planar {
+        return Ok(Err(KuratowskiWitness::from_ids(
+            edges,
+            witness.iter().copied().collect(),
+        )));
+    }
+    Ok(Ok(Embedding::from_raw(
+        edges,
+        offsets.iter().copied().collect(),
+        rotation.iter().copied().collect(),
+    )))
+}
+
+fn edge_vec(edges: &[Edge]) -> UniquePtr<CxxVector<Edge>> {
     let mut edge_buf: UniquePtr<CxxVector<Edge>> = CxxVector::new();
     {
         let mut vec = edge_buf.pin_mut();
@@ -17,10 +310,5 @@ pub fn boyer_myrvold_witness(num_verts: usize, edges: &[Edge]) -> Option<Vec<Edg
             vec.as_mut().push(e);
         }
     }
-
-    let witness: UniquePtr<CxxVector<graph::Edge>> =
-        unsafe { graph::boyer_myrvold_witness(num_verts, edge_buf.as_ref().unwrap()) };
-
-    let out: Vec<Edge> = witness.iter().map(|e| Edge { u: e.u, v: e.v }).collect();
-    (!out.is_empty()).then_some(out)
+    edge_buf
 }
@@ -1,20 +1,255 @@
+use std::{cmp::Reverse, sync::mpsc};
+
 use autocxx::moveit::Emplace;
 use cxx::{CxxVector, UniquePtr};
 
-use crate::{Edge, autogen::graph};
+use crate::{
+    Edge, Error,
+    autogen::graph,
+    embedding::Embedding,
+    error::{check_edge, check_edges},
+};
 
 /// Safe Rust wrapper around the OGDF SPQR-based `graph::PlanarSubgraph`.
+///
+/// Every call locks the C++ state, so `&self` methods may run on many
+/// threads at once. To keep reading while one thread goes on calling
+/// [`set`](Self::set), hand the readers a [`PlanarSubgraphReader`].
 pub struct PlanarSubgraph {
     inner: UniquePtr<graph::PlanarSubgraph>,
+    subscribers: Vec<Subscriber>,
+}
+
+/// Called with every non-empty delta; returning `false` unsubscribes.
+type Subscriber = Box<dyn FnMut(&[usize]) -> bool + Send>;
+
+// SAFETY: the C++ object owns all of its state (forks share only the
+// candidate list, which nobody mutates while it is shared), and every member
+// function takes its mutex, so it may be moved between threads and its const
+// members called concurrently. Subscribers are `Send` and only ever reached
+// through `&mut self`.
+unsafe impl Send for PlanarSubgraph {}
+unsafe impl Sync for PlanarSubgraph {}
+
+/// Where an addable candidate fits, or a new vertex attaches: an embedding
+/// of the selected candidates and a face of it that every endpoint lies on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessFace {
+    pub embedding: Embedding,
+    /// Boundary of the face as walks of edge ids, in the form of
+    /// [`Embedding::faces`]. There is one walk when the endpoints are
+    /// connected through selected edges. Otherwise the face joins separate
+    /// components, and there is one walk through the endpoints in each
+    /// component that has edges.
+    pub boundary: Vec<Vec<usize>>,
+}
+
+impl WitnessFace {
+    /// Pick out the face of `embedding` through `verts`. In each component,
+    /// the face through the most of them goes through all of them, given
+    /// that some face does.
+    fn through(embedding: Embedding, verts: &[usize]) -> Self {
+        let touches = |walk: &Vec<usize>, x: usize| {
+            walk.iter().any(|&e| {
+                let Edge { u, v } = embedding.edges()[e];
+                u == x || v == x
+            })
+        };
+        let mut faces = embedding.faces();
+        let mut rest = verts.to_vec();
+        let mut boundary = Vec::new();
+        while let Some(x) = rest.pop() {
+            let best = faces
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| touches(f, x))
+                .min_by_key(|(_, f)| Reverse(rest.iter().filter(|&&y| touches(f, y)).count()))
+                .map(|(k, _)| k);
+            let Some(k) = best else { continue };
+            let face = faces.swap_remove(k);
+            rest.retain(|&y| !touches(&face, y));
+            boundary.push(face);
+        }
+        WitnessFace {
+            embedding,
+            boundary,
+        }
+    }
+}
+
+/// What a candidate may do, set with [`PlanarSubgraph::set_state`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CandidateState {
+    /// Selected and deselected at will.
+    #[default]
+    Free,
+    /// Selected, and locked that way: deselecting or removing it fails.
+    ///
+    /// Its block is also kept planar: [`set`](PlanarSubgraph::set) rejects
+    /// a candidate that is not addable if the blocks it would merge include
+    /// this one's, and [`commit`](PlanarSubgraph::commit) a selection in
+    /// which this one's block is not planar. Changes elsewhere are not held
+    /// back.
+    Required,
+    /// Never addable, and cannot be selected.
+    Forbidden,
+}
+
+impl CandidateState {
+    fn to_raw(self) -> u8 {
+        match self {
+            CandidateState::Free => 0,
+            CandidateState::Required => 1,
+            CandidateState::Forbidden => 2,
+        }
+    }
+
+    fn from_raw(raw: u8) -> Self {
+        match raw {
+            1 => CandidateState::Required,
+            2 => CandidateState::Forbidden,
+            _ => CandidateState::Free,
+        }
+    }
+}
+
+/// Where [`PlanarSubgraph::contract`] moved every id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contraction {
+    /// The vertex the endpoints were merged into: the lower of the two.
+    pub vertex: usize,
+    /// New id of each vertex id. Only the higher endpoint moves; it maps to
+    /// [`vertex`](Self::vertex) and is removed.
+    pub vertices: Vec<usize>,
+    /// New id of each candidate id: itself, the lowest-id candidate it was
+    /// merged with as a parallel, or `None` if it is no longer a candidate.
+    pub candidates: Vec<Option<usize>>,
+}
+
+/// Immutable addability mask, shared with the [`PlanarSubgraph`] that
+/// published it rather than copied out, one bit per candidate id.
+///
+/// Later updates publish a new mask and leave this one untouched.
+pub struct AddableSnapshot {
+    inner: UniquePtr<graph::AddableSnapshot>,
+}
+
+// SAFETY: the snapshot is never written after it is published.
+unsafe impl Send for AddableSnapshot {}
+unsafe impl Sync for AddableSnapshot {}
+
+impl AddableSnapshot {
+    /// Number of candidate ids covered, removed ones included.
+    pub fn len(&self) -> usize {
+        unsafe { self.inner.as_ref().unwrap().len() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The packed mask: candidate `i` is bit `i % 64` of `words()[i / 64]`.
+    /// Bits past [`len`](Self::len) are zero.
+    pub fn words(&self) -> &[u64] {
+        unsafe { self.inner.as_ref().unwrap().words() }.as_slice()
+    }
+
+    /// Whether candidate `i` is addable, or `None` past the end.
+    pub fn get(&self, i: usize) -> Option<bool> {
+        (i < self.len()).then(|| self.words()[i / 64] >> (i % 64) & 1 != 0)
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Addable candidate ids, in increasing order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words().iter().enumerate().flat_map(|(k, &w)| {
+            let mut rest = w;
+            std::iter::from_fn(move || {
+                (rest != 0).then(|| {
+                    let bit = rest.trailing_zeros() as usize;
+                    rest &= rest - 1;
+                    64 * k + bit
+                })
+            })
+        })
+    }
+}
+
+/// Read-only handle on the state of a [`PlanarSubgraph`], for other threads.
+///
+/// Each call sees every update whose [`PlanarSubgraph::set`] has returned;
+/// during a transaction it sees the state at `begin`. The handle keeps the
+/// state alive after the `PlanarSubgraph` itself is dropped.
+pub struct PlanarSubgraphReader {
+    inner: UniquePtr<graph::PlanarSubgraphReader>,
+}
+
+// SAFETY: the reader shares the writer's state through a reference count
+// and locks the same mutex on every call.
+unsafe impl Send for PlanarSubgraphReader {}
+unsafe impl Sync for PlanarSubgraphReader {}
+
+impl PlanarSubgraphReader {
+    /// See [`PlanarSubgraph::num_edges`].
+    pub fn num_edges(&self) -> usize {
+        unsafe { self.inner.as_ref().unwrap().num_edges() }
+    }
+
+    /// See [`PlanarSubgraph::is_candidate`].
+    pub fn is_candidate(&self, edge_id: usize) -> bool {
+        unsafe { self.inner.as_ref().unwrap().is_candidate(edge_id) }
+    }
+
+    /// See [`PlanarSubgraph::query`].
+    pub fn query(&self) -> Vec<bool> {
+        let mask = unsafe { self.inner.as_ref().unwrap().query() };
+        mask.as_slice().iter().map(|&b| b != 0).collect()
+    }
+
+    /// See [`PlanarSubgraph::snapshot`].
+    pub fn snapshot(&self) -> AddableSnapshot {
+        AddableSnapshot {
+            inner: unsafe { self.inner.as_ref().unwrap().snapshot() },
+        }
+    }
+
+    /// See [`PlanarSubgraph::is_addable`]. The id is checked against the
+    /// state at the time of the call.
+    pub fn is_addable(&self, edge_id: usize) -> Result<bool, Error> {
+        let num_edges = self.num_edges();
+        if edge_id >= num_edges {
+            return Err(Error::EdgeOutOfRange {
+                edge: edge_id,
+                num_edges,
+            });
+        }
+        if !self.is_candidate(edge_id) {
+            return Err(Error::RemovedCandidate { edge: edge_id });
+        }
+        Ok(unsafe { self.inner.as_ref().unwrap().is_addable(edge_id) })
+    }
 }
 
 impl PlanarSubgraph {
-    pub fn new(num_verts: usize, edges_all: &[Edge], edges_added: &[bool]) -> Self {
-        assert_eq!(
-            edges_all.len(),
-            edges_added.len(),
-            "edges_all and edges_added lengths must match"
-        );
+    /// Build the structure over the candidate edges `edges_all`, selecting
+    /// those flagged in `edges_added`.
+    ///
+    /// Candidates form a multigraph. A candidate parallel to a selected edge
+    /// is always addable, since it can be drawn right next to it, and so is
+    /// every self-loop. Selecting either never blocks another candidate.
+    ///
+    /// Fails if the lengths differ or an endpoint is not below `num_verts`.
+    pub fn new(num_verts: usize, edges_all: &[Edge], edges_added: &[bool]) -> Result<Self, Error> {
+        if edges_all.len() != edges_added.len() {
+            return Err(Error::LengthMismatch {
+                edges: edges_all.len(),
+                mask: edges_added.len(),
+            });
+        }
+        check_edges(num_verts, edges_all)?;
 
         let mut edges: UniquePtr<CxxVector<graph::Edge>> = CxxVector::new();
         {
@@ -40,12 +275,416 @@ impl PlanarSubgraph {
             ))
         };
 
-        Self { inner }
+        Ok(Self {
+            inner,
+            subscribers: Vec::new(),
+        })
+    }
+
+    /// An independent copy of the current state, for branching search.
+    ///
+    /// The fork shares the candidate list with `self` until either side adds
+    /// a candidate, and copies only the per-candidate flags. Its graph and
+    /// block caches are rebuilt the first time it has to evaluate anything,
+    /// so forks that are only updated and dropped stay cheap.
+    ///
+    /// Fails while a transaction is open.
+    pub fn fork(&self) -> Result<Self, Error> {
+        self.check_no_transaction()?;
+        let inner = unsafe { self.inner.as_ref().unwrap().fork() };
+        Ok(Self {
+            inner,
+            subscribers: Vec::new(),
+        })
+    }
+
+    /// A [`PlanarSubgraphReader`] on this state, to be sent to other
+    /// threads.
+    pub fn reader(&self) -> PlanarSubgraphReader {
+        PlanarSubgraphReader {
+            inner: unsafe { self.inner.as_ref().unwrap().reader() },
+        }
+    }
+
+    /// Number of vertex ids handed out so far, including those of removed
+    /// vertices.
+    pub fn num_verts(&self) -> usize {
+        unsafe { self.inner.as_ref().unwrap().num_verts() }
+    }
+
+    /// Whether `v` names a vertex that has not been removed.
+    pub fn is_vertex(&self, v: usize) -> bool {
+        unsafe { self.inner.as_ref().unwrap().is_vertex(v) }
+    }
+
+    /// Append a new, isolated vertex and return its id. No candidate's
+    /// addability changes.
+    ///
+    /// Fails while a transaction is open.
+    pub fn add_vertex(&mut self) -> Result<usize, Error> {
+        self.check_no_transaction()?;
+        Ok(unsafe { self.inner.pin_mut().add_vertex() })
+    }
+
+    /// Remove vertex `v` along with every candidate incident to it, which
+    /// are deselected first if needed, and return the ids of those
+    /// candidates in increasing order. As with
+    /// [`remove_candidate`](Self::remove_candidate), no id is reused; later
+    /// uses of `v` fail with [`Error::RemovedVertex`].
+    ///
+    /// Fails with [`Error::Required`] if one of those candidates is
+    /// required.
+    pub fn remove_vertex(&mut self, v: usize) -> Result<Vec<usize>, Error> {
+        self.check_no_transaction()?;
+        self.check_vertex(v)?;
+        for (edge_id, e) in self.edges().into_iter().enumerate() {
+            if (e.u == v || e.v == v) && self.is_candidate(edge_id) {
+                self.check_not_required(edge_id)?;
+            }
+        }
+        if !self.subscribers.is_empty() {
+            let mut flipped: UniquePtr<CxxVector<usize>> = CxxVector::new();
+            let retired = unsafe {
+                self.inner
+                    .pin_mut()
+                    .remove_vertex_and_diff(v, flipped.pin_mut())
+            };
+            self.notify(flipped.as_slice());
+            return Ok(retired.as_slice().to_vec());
+        }
+        let retired = unsafe { self.inner.pin_mut().remove_vertex(v) };
+        Ok(retired.as_slice().to_vec())
+    }
+
+    /// Contract selected candidate `edge_id`, merging its higher endpoint
+    /// into the lower one and carrying on with the minor, which is planar
+    /// whenever the selection was.
+    ///
+    /// Each group of candidates the contraction makes parallel, that is one
+    /// with a candidate at the higher endpoint, is merged into its lowest
+    /// id, which is selected if any of the group was, and the rest are
+    /// removed. So are the loops the contraction makes: `edge_id` itself,
+    /// its parallels and the loops at the higher endpoint. Groups at the
+    /// lower endpoint that were parallel already, and its loops, are left
+    /// as they were. Ids are not reused, so the returned maps are the
+    /// identity except where something was merged or dropped. A merged
+    /// candidate is required if any of the group was, and forbidden only if
+    /// all were.
+    ///
+    /// Fails with [`Error::NotSelected`] if `edge_id` is not selected, and
+    /// while a transaction is open. A required candidate must stay selected,
+    /// so this also fails, with [`Error::Required`] naming it, if one would
+    /// be dropped as a loop.
+    pub fn contract(&mut self, edge_id: usize) -> Result<Contraction, Error> {
+        self.check_no_transaction()?;
+        self.check_edge_id(edge_id)?;
+        let Edge { u, v } = self.edge(edge_id)?;
+        let mut vertices: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut candidates: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut flipped: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let outcome = unsafe {
+            if self.subscribers.is_empty() {
+                self.inner
+                    .pin_mut()
+                    .contract(edge_id, vertices.pin_mut(), candidates.pin_mut())
+            } else {
+                self.inner.pin_mut().contract_and_diff(
+                    edge_id,
+                    vertices.pin_mut(),
+                    candidates.pin_mut(),
+                    flipped.pin_mut(),
+                )
+            }
+        };
+        match outcome {
+            0 => {}
+            1 => {
+                let edge = candidates.as_slice()[0];
+                return Err(Error::Required { edge });
+            }
+            _ => return Err(Error::NotSelected { edge: edge_id }),
+        }
+        self.notify(flipped.as_slice());
+        Ok(Contraction {
+            vertex: u.min(v),
+            vertices: vertices.as_slice().to_vec(),
+            candidates: candidates
+                .as_slice()
+                .iter()
+                .map(|&i| (i != usize::MAX).then_some(i))
+                .collect(),
+        })
+    }
+
+    /// Number of candidate ids handed out so far, including those of removed
+    /// candidates. Every per-edge mask has this length.
+    pub fn num_edges(&self) -> usize {
+        unsafe { self.inner.as_ref().unwrap().num_edges() }
+    }
+
+    /// Every candidate edge by id, removed candidates included.
+    pub fn edges(&self) -> Vec<Edge> {
+        let edges = unsafe { self.inner.as_ref().unwrap().edges() };
+        edges.as_slice().to_vec()
+    }
+
+    /// Candidate `edge_id`, removed or not, without copying the others as
+    /// [`edges`](Self::edges) does.
+    pub fn edge(&self, edge_id: usize) -> Result<Edge, Error> {
+        let num_edges = self.num_edges();
+        if edge_id >= num_edges {
+            return Err(Error::EdgeOutOfRange {
+                edge: edge_id,
+                num_edges,
+            });
+        }
+        Ok(unsafe { self.inner.as_ref().unwrap().edge(edge_id) })
+    }
+
+    /// Whether `edge_id` names a candidate that has not been removed.
+    pub fn is_candidate(&self, edge_id: usize) -> bool {
+        unsafe { self.inner.as_ref().unwrap().is_candidate(edge_id) }
+    }
+
+    /// Append a new, unselected candidate and return its id. Existing ids are
+    /// unaffected.
+    ///
+    /// Fails on an invalid edge (reported under the id it would have
+    /// received) or while a transaction is open.
+    pub fn add_candidate(&mut self, edge: Edge) -> Result<usize, Error> {
+        self.check_no_transaction()?;
+        check_edge(self.num_verts(), self.num_edges(), edge)?;
+        self.check_vertex(edge.u)?;
+        self.check_vertex(edge.v)?;
+        Ok(unsafe { self.inner.pin_mut().add_candidate(&edge) })
+    }
+
+    /// Remove candidate `edge_id`, deselecting it first if needed. Its id is
+    /// never reused; later uses of it fail with [`Error::RemovedCandidate`],
+    /// and masks report it as not addable.
+    ///
+    /// Fails with [`Error::Required`] if the candidate is required.
+    pub fn remove_candidate(&mut self, edge_id: usize) -> Result<(), Error> {
+        self.check_no_transaction()?;
+        self.check_edge_id(edge_id)?;
+        self.check_not_required(edge_id)?;
+        if !self.subscribers.is_empty() {
+            let flipped = unsafe { self.inner.pin_mut().remove_candidate_and_diff(edge_id) };
+            self.notify(flipped.as_slice());
+            return Ok(());
+        }
+        unsafe { self.inner.pin_mut().remove_candidate(edge_id) };
+        Ok(())
+    }
+
+    /// The state of candidate `edge_id`; every candidate starts out
+    /// [`Free`](CandidateState::Free).
+    pub fn state(&self, edge_id: usize) -> Result<CandidateState, Error> {
+        self.check_edge_id(edge_id)?;
+        let raw = unsafe { self.inner.as_ref().unwrap().state(edge_id) };
+        Ok(CandidateState::from_raw(raw))
+    }
+
+    /// Change the state of candidate `edge_id`. Making it
+    /// [`Required`](CandidateState::Required) selects it, and making it
+    /// [`Forbidden`](CandidateState::Forbidden) deselects it. While it is
+    /// required, [`set`](Self::set) and [`commit`](Self::commit) keep its
+    /// block planar.
+    ///
+    /// Fails with [`Error::NotAddable`] when requiring a candidate that is
+    /// neither selected nor addable, and while a transaction is open.
+    pub fn set_state(&mut self, edge_id: usize, state: CandidateState) -> Result<(), Error> {
+        self.check_no_transaction()?;
+        self.check_edge_id(edge_id)?;
+        let mut flipped: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let ok = unsafe {
+            if self.subscribers.is_empty() {
+                self.inner.pin_mut().set_state(edge_id, state.to_raw())
+            } else {
+                self.inner
+                    .pin_mut()
+                    .set_state_and_diff(edge_id, state.to_raw(), flipped.pin_mut())
+            }
+        };
+        if !ok {
+            return Err(Error::NotAddable { edge: edge_id });
+        }
+        self.notify(flipped.as_slice());
+        Ok(())
     }
 
-    pub fn set(&mut self, edge_id: usize, present: bool) {
+    /// Select (`present = true`) or deselect candidate `edge_id`.
+    ///
+    /// Fails with [`Error::Forbidden`] when selecting a forbidden candidate
+    /// and [`Error::Required`] when deselecting a required one. Selecting
+    /// one that is not addable fails with [`Error::NotAddable`] if a
+    /// required candidate lies in one of the blocks it would merge; within a
+    /// transaction that check waits for [`commit`](Self::commit). Any other
+    /// free candidate may be selected.
+    pub fn set(&mut self, edge_id: usize, present: bool) -> Result<(), Error> {
+        self.check_set(edge_id, present)?;
         let mut inner = self.inner.pin_mut();
         unsafe { inner.as_mut().set(edge_id, present) };
+        Ok(())
+    }
+
+    /// [`set`](Self::set), returning the candidates whose addability changed
+    /// as a result, in increasing order. Only candidates whose block-cut path
+    /// crosses a block the update may change, or that it may connect, are
+    /// evaluated before and after it, so the cost follows the blocks the
+    /// update touches rather than the number of candidates. The others are
+    /// still scanned once, cheaply, to find them.
+    ///
+    /// Fails while a transaction is open; [`commit_and_diff`] reports the
+    /// delta of a whole transaction instead.
+    ///
+    /// [`commit_and_diff`]: Self::commit_and_diff
+    pub fn set_and_diff(&mut self, edge_id: usize, present: bool) -> Result<Vec<usize>, Error> {
+        self.check_no_transaction()?;
+        self.check_set(edge_id, present)?;
+        let flipped = unsafe { self.inner.pin_mut().set_and_diff(edge_id, present) };
+        let flipped = flipped.as_slice().to_vec();
+        self.notify(&flipped);
+        Ok(flipped)
+    }
+
+    /// Select each candidate of `order` that is still addable when reached,
+    /// and return the ones selected in the order they were. Candidates
+    /// already selected are left alone. Applied to every candidate, this
+    /// fills the selection to a maximal planar subgraph.
+    ///
+    /// The result is that of a [`set`](Self::set) loop, but the candidates
+    /// are taken in batches under one lock. A batch of candidates addable on
+    /// their own is checked with one planarity test over the blocks it may
+    /// merge and, if it keeps the selection planar, selected as one update;
+    /// otherwise a binary search finds its longest planar prefix, and the
+    /// fill goes on from the candidate that broke it. Batches double while
+    /// they succeed and halve when they do not, so a block's SPQR tree is
+    /// rebuilt once per batch that changes it rather than once per
+    /// selection, and each rejected candidate costs `O(log batch)` tests.
+    ///
+    /// With subscribers, only candidates whose path crosses a block that
+    /// `order` may change, or that `order` may connect, are compared.
+    ///
+    /// Fails while a transaction is open, or if `order` names an id that is
+    /// not a candidate, in which case nothing is selected.
+    pub fn fill_greedy(&mut self, order: &[usize]) -> Result<Vec<usize>, Error> {
+        self.check_no_transaction()?;
+        let mut ids: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        {
+            let mut vec = ids.pin_mut();
+            for &i in order {
+                self.check_edge_id(i)?;
+                vec.as_mut().push(i);
+            }
+        }
+        let order = ids.as_ref().unwrap();
+        let selected = if self.subscribers.is_empty() {
+            unsafe { self.inner.pin_mut().fill_greedy(order) }
+        } else {
+            let mut flipped: UniquePtr<CxxVector<usize>> = CxxVector::new();
+            let selected = unsafe {
+                self.inner
+                    .pin_mut()
+                    .fill_greedy_and_diff(order, flipped.pin_mut())
+            };
+            self.notify(flipped.as_slice());
+            selected
+        };
+        Ok(selected.as_slice().to_vec())
+    }
+
+    /// [`fill_greedy`](Self::fill_greedy) in order of decreasing
+    /// `priorities[i]`, ties going to the lower id. Removed candidates are
+    /// skipped.
+    ///
+    /// Fails if there is not one priority per candidate id.
+    pub fn fill_greedy_weighted(&mut self, priorities: &[f64]) -> Result<Vec<usize>, Error> {
+        if priorities.len() != self.num_edges() {
+            return Err(Error::LengthMismatch {
+                edges: self.num_edges(),
+                mask: priorities.len(),
+            });
+        }
+        let mut order: Vec<usize> = (0..priorities.len())
+            .filter(|&i| self.is_candidate(i))
+            .collect();
+        order.sort_by(|&a, &b| priorities[b].total_cmp(&priorities[a]));
+        self.fill_greedy(&order)
+    }
+
+    /// Call `f` with the candidates whose addability changed, after every
+    /// [`set_and_diff`](Self::set_and_diff),
+    /// [`commit_and_diff`](Self::commit_and_diff),
+    /// [`fill_greedy`](Self::fill_greedy),
+    /// [`remove_candidate`](Self::remove_candidate),
+    /// [`remove_vertex`](Self::remove_vertex),
+    /// [`contract`](Self::contract) or [`set_state`](Self::set_state) that
+    /// changed any. Plain [`set`](Self::set) and [`commit`](Self::commit)
+    /// stay lazy and notify nobody.
+    /// Subscribers are not carried over to forks.
+    pub fn on_change(&mut self, mut f: impl FnMut(&[usize]) + Send + 'static) {
+        self.subscribers.push(Box::new(move |flipped| {
+            f(flipped);
+            true
+        }));
+    }
+
+    /// [`on_change`](Self::on_change) through a channel. The subscription
+    /// ends once the receiver is dropped.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<Vec<usize>> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers
+            .push(Box::new(move |flipped| tx.send(flipped.to_vec()).is_ok()));
+        rx
+    }
+
+    fn notify(&mut self, flipped: &[usize]) {
+        if !flipped.is_empty() {
+            self.subscribers.retain_mut(|f| f(flipped));
+        }
+    }
+
+    /// Open a transaction. Until [`commit`](Self::commit) or
+    /// [`rollback`](Self::rollback), [`set`](Self::set) only records the new
+    /// selection, and queries keep answering for the state at `begin`.
+    pub fn begin(&mut self) -> Result<(), Error> {
+        let ok = unsafe { self.inner.pin_mut().begin() };
+        ok.then_some(()).ok_or(Error::TransactionActive)
+    }
+
+    /// Close the open transaction, applying every edge it changed in one
+    /// update.
+    ///
+    /// Fails with [`Error::NotPlanar`] if a required candidate lies in a
+    /// block of the new selection that is not planar, leaving the
+    /// transaction open. Any other selection commits.
+    pub fn commit(&mut self) -> Result<(), Error> {
+        self.check_commit()?;
+        let ok = unsafe { self.inner.pin_mut().commit() };
+        ok.then_some(()).ok_or(Error::NoTransaction)
+    }
+
+    /// [`commit`](Self::commit), returning the candidates whose addability
+    /// differs from before the transaction, as
+    /// [`set_and_diff`](Self::set_and_diff) does.
+    pub fn commit_and_diff(&mut self) -> Result<Vec<usize>, Error> {
+        self.check_commit()?;
+        let mut flipped: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let ok = unsafe { self.inner.pin_mut().commit_and_diff(flipped.pin_mut()) };
+        if !ok {
+            return Err(Error::NoTransaction);
+        }
+        let flipped = flipped.as_slice().to_vec();
+        self.notify(&flipped);
+        Ok(flipped)
+    }
+
+    /// Close the open transaction, restoring the selection at `begin`. The
+    /// addability mask never moved, so nothing is invalidated.
+    pub fn rollback(&mut self) -> Result<(), Error> {
+        let ok = unsafe { self.inner.pin_mut().rollback() };
+        ok.then_some(()).ok_or(Error::NoTransaction)
     }
 
     pub fn query(&self) -> Vec<bool> {
@@ -61,6 +700,332 @@ impl PlanarSubgraph {
             .collect()
     }
 
+    /// Whether candidate `edge_id` can currently be selected while keeping
+    /// the selection planar. Selected candidates are never addable.
+    ///
+    /// Unlike [`query`](Self::query), this evaluates only the one candidate,
+    /// so a caller watching a few edges does not pay for the rest.
+    pub fn is_addable(&self, edge_id: usize) -> Result<bool, Error> {
+        self.check_edge_id(edge_id)?;
+        Ok(unsafe { self.inner.as_ref().unwrap().is_addable(edge_id) })
+    }
+
+    /// [`is_addable`](Self::is_addable) for each of `edge_ids`, in order.
+    pub fn addable_among(&self, edge_ids: &[usize]) -> Result<Vec<bool>, Error> {
+        let mut ids: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        {
+            let mut vec = ids.pin_mut();
+            for &i in edge_ids {
+                self.check_edge_id(i)?;
+                vec.as_mut().push(i);
+            }
+        }
+        let mask = unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .addable_among(ids.as_ref().unwrap())
+        };
+        Ok(mask.as_slice().iter().map(|&b| b != 0).collect())
+    }
+
+    /// Test instrumentation, hidden from the docs and exempt from semver:
+    /// how many times a candidate's addability has been worked out from the
+    /// decomposition so far. An update only makes answers stale whose path
+    /// through the block-cut tree crosses a block it changed; the rest are
+    /// reused, and the cheap cases (selected, forbidden, loops, parallels of
+    /// selected candidates, endpoints in different components) are never
+    /// counted.
+    #[doc(hidden)]
+    pub fn evaluations(&self) -> usize {
+        unsafe { self.inner.as_ref().unwrap().evaluations() }
+    }
+
+    /// Explain why candidate `edge_id` is not addable: the sorted ids of a
+    /// Kuratowski subgraph formed by selected candidates and `edge_id`
+    /// itself. Look them up among the candidate edges to classify the
+    /// witness with [`kuratowski_subdivision`].
+    ///
+    /// Returns `None` if the candidate is selected or addable. Should the
+    /// selection already be non-planar, the witness may not involve
+    /// `edge_id` at all.
+    ///
+    /// [`kuratowski_subdivision`]: crate::mps::kuratowski_subdivision
+    pub fn obstruction(&self, edge_id: usize) -> Result<Option<Vec<usize>>, Error> {
+        self.check_edge_id(edge_id)?;
+        let mut ids: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let found = unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .obstruction(edge_id, ids.pin_mut())
+        };
+        Ok(found.then(|| ids.as_slice().to_vec()))
+    }
+
+    /// A planar embedding of the selected candidates. Rotations list
+    /// candidate ids, and [`Embedding::edges`] is the full candidate list.
+    ///
+    /// This is not read off the decomposition behind the addability
+    /// answers: each call runs its own Boyer-Myrvold pass over the
+    /// selection, costing linear time, and nothing ties the result to those
+    /// answers. An addable candidate may well have its endpoints on no common
+    /// face of it; use [`witness_face`](Self::witness_face) for an embedding
+    /// that fits one. Returns `None` only if the selection is itself
+    /// non-planar, which takes selecting a candidate that was not addable.
+    pub fn embedding(&self) -> Option<Embedding> {
+        let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut rotation: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let planar = unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .embedding(offsets.pin_mut(), rotation.pin_mut())
+        };
+        planar.then(|| {
+            Embedding::from_raw(
+                &self.edges(),
+                offsets.as_slice().to_vec(),
+                rotation.as_slice().to_vec(),
+            )
+        })
+    }
+
+    /// Show why candidate `edge_id` is addable: an embedding of the selection
+    /// with a face on which it can be drawn. Returns `None` if the candidate
+    /// is selected or not addable.
+    pub fn witness_face(&self, edge_id: usize) -> Result<Option<WitnessFace>, Error> {
+        self.check_edge_id(edge_id)?;
+        let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut rotation: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let found = unsafe {
+            self.inner.as_ref().unwrap().witness_embedding(
+                edge_id,
+                offsets.pin_mut(),
+                rotation.pin_mut(),
+            )
+        };
+        if !found {
+            return Ok(None);
+        }
+
+        let edges = self.edges();
+        let Edge { u, v } = edges[edge_id];
+        let embedding = Embedding::from_raw(
+            &edges,
+            offsets.as_slice().to_vec(),
+            rotation.as_slice().to_vec(),
+        );
+        Ok(Some(WitnessFace::through(embedding, &[v, u])))
+    }
+
+    /// Whether a new vertex adjacent to every one of `verts` could join the
+    /// selection, that is, whether they all lie on one face of some
+    /// embedding of it. Fewer than two vertices always can, and for two this
+    /// is [`can_add`](Self::can_add).
+    pub fn can_attach_vertex(&self, verts: &[usize]) -> Result<bool, Error> {
+        let verts = self.vertex_list(verts)?;
+        Ok(unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .can_attach(verts.as_ref().unwrap())
+        })
+    }
+
+    /// Show where a new vertex adjacent to every one of `verts` fits: an
+    /// embedding of the selection with a face that they all lie on. Returns
+    /// `None` if [`can_attach_vertex`](Self::can_attach_vertex) would say
+    /// no, or if the selection is not planar.
+    pub fn attach_face(&self, verts: &[usize]) -> Result<Option<WitnessFace>, Error> {
+        let ids = self.vertex_list(verts)?;
+        let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut rotation: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let found = unsafe {
+            self.inner.as_ref().unwrap().attach_embedding(
+                ids.as_ref().unwrap(),
+                offsets.pin_mut(),
+                rotation.pin_mut(),
+            )
+        };
+        if !found {
+            return Ok(None);
+        }
+        let embedding = Embedding::from_raw(
+            &self.edges(),
+            offsets.as_slice().to_vec(),
+            rotation.as_slice().to_vec(),
+        );
+        let mut verts = verts.to_vec();
+        verts.sort_unstable_by(|a, b| b.cmp(a));
+        verts.dedup();
+        Ok(Some(WitnessFace::through(embedding, &verts)))
+    }
+
+    /// Whether an edge between vertices `u` and `v` could join the
+    /// selection without breaking planarity, whether or not it was declared
+    /// as a candidate. A pair that is already adjacent is always addable.
+    pub fn can_add(&self, u: usize, v: usize) -> Result<bool, Error> {
+        self.check_vertex(u)?;
+        self.check_vertex(v)?;
+        Ok(unsafe { self.inner.as_ref().unwrap().can_add(u, v) })
+    }
+
+    /// Every vertex other than `v` that [`can_add`](Self::can_add) would
+    /// accept as a partner of `v`, in increasing order.
+    pub fn addable_partners(&self, v: usize) -> Result<Vec<usize>, Error> {
+        self.check_vertex(v)?;
+        let partners = unsafe { self.inner.as_ref().unwrap().addable_partners(v) };
+        Ok(partners.as_slice().to_vec())
+    }
+
+    /// Whether the candidates `edge_ids` could all be selected together. The
+    /// inner result is `Ok(())` if so, and otherwise the sorted ids of a
+    /// minimal subset of `edge_ids` that cannot: dropping any one of them
+    /// would leave the rest addable together. The subset is empty if the
+    /// selection itself is not planar, and a forbidden candidate makes up
+    /// one by itself.
+    ///
+    /// Nothing is selected, and the cached decomposition is not touched:
+    /// the test runs on a copy of the selection.
+    pub fn can_add_all(&self, edge_ids: &[usize]) -> Result<Result<(), Vec<usize>>, Error> {
+        let mut ids: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        {
+            let mut vec = ids.pin_mut();
+            for &i in edge_ids {
+                self.check_edge_id(i)?;
+                vec.as_mut().push(i);
+            }
+        }
+        let mut conflict: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let ok = unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .can_add_all(ids.as_ref().unwrap(), conflict.pin_mut())
+        };
+        Ok(if ok {
+            Ok(())
+        } else {
+            Err(conflict.as_slice().to_vec())
+        })
+    }
+
+    /// The currently addable candidates that would stop being addable if
+    /// `edge_id` were selected, in increasing order. Empty if `edge_id` is
+    /// not addable itself.
+    ///
+    /// Nothing is selected to find out: this builds an SPQR tree over the
+    /// blocks `edge_id` would merge, then walks the block-cut tree path of
+    /// each candidate in its component, so it costs about as much as one
+    /// [`set`](Self::set) followed by a [`query`](Self::query) of that
+    /// component.
+    pub fn conflicts(&self, edge_id: usize) -> Result<Vec<usize>, Error> {
+        self.check_edge_id(edge_id)?;
+        let ids = unsafe { self.inner.as_ref().unwrap().conflicts(edge_id) };
+        Ok(ids.as_slice().to_vec())
+    }
+
+    /// [`conflicts`](Self::conflicts) for every candidate at once, indexed by
+    /// edge id. Two addable candidates conflict exactly when they cannot
+    /// both be added, so the lists describe a symmetric graph.
+    ///
+    /// This runs [`conflicts`](Self::conflicts) once per addable candidate,
+    /// so on a connected selection with `m` candidates it takes `O(m)` SPQR
+    /// tree builds and `O(m²)` path walks.
+    pub fn conflict_graph(&self) -> Vec<Vec<usize>> {
+        let mut offsets: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        let mut neighbours: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        unsafe {
+            self.inner
+                .as_ref()
+                .unwrap()
+                .conflict_graph(offsets.pin_mut(), neighbours.pin_mut())
+        };
+        let neighbours = neighbours.as_slice();
+        offsets
+            .as_slice()
+            .windows(2)
+            .map(|w| neighbours[w[0]..w[1]].to_vec())
+            .collect()
+    }
+
+    /// The addability mask of [`query`](Self::query) as a shared, packed
+    /// snapshot. Nothing is copied when the mask is already up to date, so
+    /// polling this is cheap.
+    pub fn snapshot(&self) -> AddableSnapshot {
+        AddableSnapshot {
+            inner: unsafe { self.inner.as_ref().unwrap().snapshot() },
+        }
+    }
+
+    fn check_edge_id(&self, edge: usize) -> Result<(), Error> {
+        let num_edges = self.num_edges();
+        if edge >= num_edges {
+            return Err(Error::EdgeOutOfRange { edge, num_edges });
+        }
+        if !self.is_candidate(edge) {
+            return Err(Error::RemovedCandidate { edge });
+        }
+        Ok(())
+    }
+
+    fn check_vertex(&self, vertex: usize) -> Result<(), Error> {
+        let num_verts = self.num_verts();
+        if vertex >= num_verts {
+            return Err(Error::NoSuchVertex { vertex, num_verts });
+        }
+        if !self.is_vertex(vertex) {
+            return Err(Error::RemovedVertex { vertex });
+        }
+        Ok(())
+    }
+
+    fn vertex_list(&self, verts: &[usize]) -> Result<UniquePtr<CxxVector<usize>>, Error> {
+        let mut out: UniquePtr<CxxVector<usize>> = CxxVector::new();
+        {
+            let mut vec = out.pin_mut();
+            for &v in verts {
+                self.check_vertex(v)?;
+                vec.as_mut().push(v);
+            }
+        }
+        Ok(out)
+    }
+
+    fn check_not_required(&self, edge_id: usize) -> Result<(), Error> {
+        match self.state(edge_id)? {
+            CandidateState::Required => Err(Error::Required { edge: edge_id }),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_set(&self, edge_id: usize, present: bool) -> Result<(), Error> {
+        self.check_edge_id(edge_id)?;
+        let outcome = unsafe { self.inner.as_ref().unwrap().check_set(edge_id, present) };
+        match outcome {
+            0 => Ok(()),
+            1 => Err(Error::Required { edge: edge_id }),
+            2 => Err(Error::Forbidden { edge: edge_id }),
+            _ => Err(Error::NotAddable { edge: edge_id }),
+        }
+    }
+
+    fn check_commit(&self) -> Result<(), Error> {
+        match unsafe { self.inner.as_ref().unwrap().check_commit() } {
+            0 => Ok(()),
+            4 => Err(Error::NotPlanar),
+            _ => Err(Error::NoTransaction),
+        }
+    }
+
+    fn check_no_transaction(&self) -> Result<(), Error> {
+        if unsafe { self.inner.as_ref().unwrap().in_transaction() } {
+            return Err(Error::TransactionActive);
+        }
+        Ok(())
+    }
+
     /// Expose the raw C++ pointer if you ever need to call other C++ APIs.
     pub fn as_raw(&self) -> &UniquePtr<graph::PlanarSubgraph> {
         &self.inner
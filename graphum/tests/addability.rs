@@ -11,7 +11,7 @@ fn spqr_addability_matches_networkx_on_incremental_planar_build() {
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let tri1 = [edge(0, 1), edge(1, 2), edge(2, 0)];
     let tri2 = [edge(5, 6), edge(6, 7), edge(7, 5)];
     let bridge = [edge(2, 5)];
@@ -20,7 +20,7 @@ fn spqr_addability_matches_networkx_on_incremental_planar_build() {
     let mut h_idx = Vec::new();
     for e in tri1.iter().chain(tri2.iter()).chain(bridge.iter()) {
         let k = pair2idx[e];
-        ps.set(k, true);
+        ps.set(k, true).unwrap();
         h_idx.push(k);
     }
 
@@ -36,14 +36,14 @@ fn spqr_dynamic_add_remove_consistency_with_greedy() {
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mut keep_idx = planarize_edges_greedy(n, &edges);
     keep_idx.sort_unstable();
 
     let mut selected: HashSet<usize> = HashSet::new();
     for &i in &keep_idx {
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
         selected.insert(i);
     }
 
@@ -58,7 +58,7 @@ fn spqr_dynamic_add_remove_consistency_with_greedy() {
         .copied()
         .collect::<Vec<_>>();
     for ridx in to_remove {
-        ps.set(ridx, false);
+        ps.set(ridx, false).unwrap();
         selected.remove(&ridx);
         let spqr_mask = common::mask_from_spqr(&ps);
         let truth_mask = truth_addability_mask(n, &selected, &edges);
@@ -75,7 +75,7 @@ fn spqr_dynamic_add_remove_consistency_with_greedy() {
             break;
         }
         let i = candidates[0];
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
         selected.insert(i);
         let spqr_mask = common::mask_from_spqr(&ps);
         let truth_mask = truth_addability_mask(n, &selected, &edges);
@@ -88,7 +88,7 @@ fn spqr_addability_cross_components_all_addable() {
     let n = 8;
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
-    let ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mask = common::mask_from_spqr(&ps);
     assert_eq!(mask.len(), edges.len());
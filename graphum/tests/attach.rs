@@ -0,0 +1,125 @@
+mod common;
+
+use common::{boyer_myrvold, edge, edges_complete, edges_random, mk_edges};
+use rand::{RngExt, seq::IndexedRandom};
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+/// The octahedron, with antipodal pairs `(0, 1)`, `(2, 3)` and `(4, 5)`.
+/// Its faces are the triangles taking one vertex from each pair.
+fn octahedron() -> Vec<Edge> {
+    edges_complete(6)
+        .into_iter()
+        .filter(|e| e.u / 2 != e.v / 2)
+        .collect()
+}
+
+fn truth_attach(n: usize, selected: &[Edge], verts: &[usize]) -> bool {
+    let mut with = selected.to_vec();
+    with.extend(verts.iter().map(|&v| edge(v, n)));
+    boyer_myrvold(n + 1, &with).0
+}
+
+fn touches(wf: &graphum::spqr::WitnessFace, walk: &[usize], x: usize) -> bool {
+    walk.iter().any(|&e| {
+        let Edge { u, v } = wf.embedding.edges()[e];
+        u == x || v == x
+    })
+}
+
+#[test]
+fn octahedron_faces_take_hubs() {
+    let edges = octahedron();
+    let ps = PlanarSubgraph::new(6, &edges, &vec![true; edges.len()]).unwrap();
+
+    assert!(ps.can_attach_vertex(&[]).unwrap());
+    assert!(ps.can_attach_vertex(&[3]).unwrap());
+    assert!(ps.can_attach_vertex(&[0, 2, 4]).unwrap());
+    assert!(ps.can_attach_vertex(&[1, 3, 4, 1]).unwrap());
+    assert!(!ps.can_attach_vertex(&[0, 1]).unwrap());
+    assert!(!ps.can_attach_vertex(&[0, 2, 4, 5]).unwrap());
+
+    let wf = ps.attach_face(&[1, 3, 4]).unwrap().unwrap();
+    assert_eq!(wf.boundary.len(), 1);
+    assert_eq!(wf.boundary[0].len(), 3);
+    for x in [1, 3, 4] {
+        assert!(touches(&wf, &wf.boundary[0], x));
+    }
+    assert!(ps.attach_face(&[0, 1]).unwrap().is_none());
+}
+
+#[test]
+fn attaching_across_components_gives_a_walk_each() {
+    let mut edges = mk_edges(&edges_complete(4));
+    edges.extend(edges_complete(4).iter().map(|e| edge(e.u + 4, e.v + 4)));
+    let ps = PlanarSubgraph::new(9, &edges, &vec![true; edges.len()]).unwrap();
+
+    assert!(ps.can_attach_vertex(&[0, 1, 2, 5, 6, 7, 8]).unwrap());
+    // Every pair of a K4 is adjacent, but no face holds all four.
+    assert!(!ps.can_attach_vertex(&[0, 1, 2, 3]).unwrap());
+
+    let wf = ps.attach_face(&[8, 0, 1, 2, 5, 6, 7]).unwrap().unwrap();
+    assert_eq!(wf.boundary.len(), 2);
+    for (walk, verts) in wf.boundary.iter().zip([[0, 1, 2], [5, 6, 7]]) {
+        for x in verts {
+            assert!(touches(&wf, walk, x));
+        }
+    }
+}
+
+#[test]
+fn random_attachments_match_truth() {
+    let mut rng = common::rng(0xA77A);
+    let n = 14;
+    let edges = edges_random(n, 50, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = Vec::new();
+
+    for step in 0..30 {
+        let mask = ps.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        if let Some(&i) = legal.choose(&mut rng) {
+            ps.set(i, true).unwrap();
+            selected.push(edges[i]);
+        }
+
+        let verts: Vec<usize> = (0..n).collect();
+        for _ in 0..10 {
+            let k = rng.random_range(0..6);
+            let s: Vec<usize> = verts.sample(&mut rng, k).copied().collect();
+            let expect = truth_attach(n, &selected, &s);
+            assert_eq!(
+                ps.can_attach_vertex(&s).unwrap(),
+                expect,
+                "step {step}, {s:?}"
+            );
+
+            let wf = ps.attach_face(&s).unwrap();
+            assert_eq!(wf.is_some(), expect, "step {step}, {s:?}");
+            let Some(wf) = wf else { continue };
+            for &x in &s {
+                let on = wf.boundary.iter().any(|walk| touches(&wf, walk, x));
+                let isolated = !selected.iter().any(|e| e.u == x || e.v == x);
+                assert!(on || isolated, "step {step}, vertex {x}");
+            }
+        }
+    }
+}
+
+#[test]
+fn attach_rejects_bad_vertices() {
+    let edges = mk_edges(&edges_complete(4));
+    let mut ps = PlanarSubgraph::new(5, &edges, &vec![true; edges.len()]).unwrap();
+    assert_eq!(
+        ps.can_attach_vertex(&[0, 5]),
+        Err(Error::NoSuchVertex {
+            vertex: 5,
+            num_verts: 5
+        })
+    );
+    ps.remove_vertex(4).unwrap();
+    assert_eq!(
+        ps.attach_face(&[0, 4]),
+        Err(Error::RemovedVertex { vertex: 4 })
+    );
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{boyer_myrvold, edge, edges_complete, edges_random, mk_edges, pair_index};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+fn planar_with(n: usize, edges: &[Edge], selected: &HashSet<usize>, extra: &[usize]) -> bool {
+    let sub: Vec<Edge> = selected.iter().chain(extra).map(|&i| edges[i]).collect();
+    boyer_myrvold(n, &sub).0
+}
+
+#[test]
+fn mutually_exclusive_pair_is_reported() {
+    let n = 5;
+    let pairs = edges_complete(n);
+    let edges = mk_edges(&pairs);
+    let idx = pair_index(&pairs);
+    let (a, b) = (idx[&edge(0, 1)], idx[&edge(2, 3)]);
+    let added: Vec<bool> = (0..edges.len()).map(|i| i != a && i != b).collect();
+    let ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+
+    assert!(ps.is_addable(a).unwrap() && ps.is_addable(b).unwrap());
+    assert_eq!(ps.can_add_all(&[a]).unwrap(), Ok(()));
+    assert_eq!(
+        ps.can_add_all(&[b, a]).unwrap(),
+        Err(vec![a.min(b), a.max(b)])
+    );
+    assert_eq!(ps.can_add_all(&[]).unwrap(), Ok(()));
+    assert_eq!(ps.can_add_all(&[a, 4]).unwrap(), Ok(()));
+    assert_eq!(
+        ps.can_add_all(&[a, 10]),
+        Err(Error::EdgeOutOfRange {
+            edge: 10,
+            num_edges: 10
+        })
+    );
+}
+
+#[test]
+fn random_batches_match_truth_with_minimal_conflicts() {
+    let mut rng = common::rng(0xBA7C);
+    let n = 12;
+    let edges = edges_random(n, 45, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+
+    for step in 0..40 {
+        let mask = ps.query();
+        let addable: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        if addable.is_empty() {
+            break;
+        }
+        let k = rng.random_range(1..=addable.len().min(8));
+        let batch: Vec<usize> = addable.sample(&mut rng, k).copied().collect();
+
+        match ps.can_add_all(&batch).unwrap() {
+            Ok(()) => assert!(planar_with(n, &edges, &selected, &batch), "step {step}"),
+            Err(conflict) => {
+                assert!(!conflict.is_empty());
+                assert!(conflict.windows(2).all(|w| w[0] < w[1]));
+                assert!(conflict.iter().all(|i| batch.contains(i)));
+                assert!(!planar_with(n, &edges, &selected, &conflict));
+                for &drop in &conflict {
+                    let rest: Vec<usize> =
+                        conflict.iter().copied().filter(|&i| i != drop).collect();
+                    assert!(planar_with(n, &edges, &selected, &rest), "step {step}");
+                }
+            }
+        }
+        assert_eq!(ps.query(), mask, "step {step}");
+
+        let &i = addable.choose(&mut rng).unwrap();
+        ps.set(i, true).unwrap();
+        selected.insert(i);
+    }
+}
+
+#[test]
+fn loops_and_parallels_never_conflict() {
+    let n = 5;
+    let mut edges = mk_edges(&edges_complete(n));
+    let m = edges.len();
+    edges.push(Edge { u: 3, v: 3 });
+    edges.push(edges[0]);
+    let mut added = vec![true; m - 1];
+    added.extend([false, false, false]);
+    let ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+
+    assert_eq!(ps.can_add_all(&[m, m + 1, 0]).unwrap(), Ok(()));
+    assert_eq!(ps.can_add_all(&[m, m - 1]).unwrap(), Err(vec![m - 1]));
+}
@@ -0,0 +1,292 @@
+mod common;
+
+use common::{edge, edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::{
+    Edge, Error,
+    spqr::{CandidateState, PlanarSubgraph},
+};
+
+/// K5 with every edge but `(0, 1)` selected, and a function giving the id
+/// of each pair.
+fn k5_minus_edge() -> (PlanarSubgraph, impl Fn(usize, usize) -> usize) {
+    let edges = mk_edges(&edges_complete(5));
+    let id = move |u, v| edges.iter().position(|&e| e == edge(u, v)).unwrap();
+    let missing = id(0, 1);
+    let added: Vec<bool> = (0..10).map(|i| i != missing).collect();
+    (
+        PlanarSubgraph::new(5, &mk_edges(&edges_complete(5)), &added).unwrap(),
+        id,
+    )
+}
+
+#[test]
+fn forbidden_candidates_are_never_addable() {
+    let edges = mk_edges(&edges_complete(4));
+    let mut ps = PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).unwrap();
+    assert_eq!(ps.state(2), Ok(CandidateState::Free));
+
+    ps.set_state(2, CandidateState::Forbidden).unwrap();
+    assert_eq!(ps.state(2), Ok(CandidateState::Forbidden));
+    assert!(!ps.is_addable(2).unwrap());
+    assert!(!ps.query()[2]);
+    assert!(!ps.snapshot().get(2).unwrap());
+    assert_eq!(ps.set(2, true), Err(Error::Forbidden { edge: 2 }));
+    assert_eq!(ps.can_add_all(&[0, 2]).unwrap(), Err(vec![2]));
+    assert!(ps.witness_face(2).unwrap().is_none());
+
+    let order: Vec<usize> = (0..edges.len()).collect();
+    assert_eq!(ps.fill_greedy(&order).unwrap(), vec![0, 1, 3, 4, 5]);
+
+    ps.set_state(2, CandidateState::Free).unwrap();
+    assert!(ps.is_addable(2).unwrap());
+}
+
+#[test]
+fn forbidding_a_selected_candidate_deselects_it() {
+    let (mut ps, id) = k5_minus_edge();
+    let missing = id(0, 1);
+    assert!(!ps.is_addable(missing).unwrap());
+    ps.set_state(id(2, 3), CandidateState::Forbidden).unwrap();
+    assert!(!ps.query()[id(2, 3)]);
+    assert!(ps.is_addable(missing).unwrap());
+}
+
+#[test]
+fn required_candidates_are_locked_in() {
+    let edges = mk_edges(&edges_complete(4));
+    let mut ps = PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).unwrap();
+    ps.set_state(0, CandidateState::Required).unwrap();
+    assert!(!ps.is_addable(0).unwrap());
+
+    let Edge { u, .. } = edges[0];
+    assert_eq!(ps.set(0, false), Err(Error::Required { edge: 0 }));
+    assert_eq!(ps.set(0, true), Ok(()));
+    assert_eq!(ps.remove_candidate(0), Err(Error::Required { edge: 0 }));
+    assert_eq!(ps.remove_vertex(u), Err(Error::Required { edge: 0 }));
+    assert!(ps.is_vertex(u));
+
+    ps.set_state(0, CandidateState::Free).unwrap();
+    ps.set(0, false).unwrap();
+    assert!(ps.is_addable(0).unwrap());
+}
+
+#[test]
+fn contracting_never_drops_a_required_candidate() {
+    // a doubled edge (0, 1) and a pendant (1, 2), all selected
+    let edges = vec![edge(0, 1), edge(0, 1), edge(1, 2)];
+    let mut ps = PlanarSubgraph::new(3, &edges, &[true; 3]).unwrap();
+
+    ps.set_state(0, CandidateState::Required).unwrap();
+    assert_eq!(ps.contract(0), Err(Error::Required { edge: 0 }));
+    ps.set_state(0, CandidateState::Free).unwrap();
+    ps.set_state(1, CandidateState::Required).unwrap();
+    let mask = ps.query();
+    assert_eq!(ps.contract(0), Err(Error::Required { edge: 1 }));
+    assert!(ps.is_candidate(1) && ps.is_vertex(0) && ps.is_vertex(1));
+    assert_eq!(ps.state(1), Ok(CandidateState::Required));
+    assert_eq!(ps.query(), mask);
+
+    ps.set_state(1, CandidateState::Free).unwrap();
+    let c = ps.contract(0).unwrap();
+    assert_eq!(c.candidates, vec![None, None, Some(2)]);
+    assert!(!ps.is_candidate(1));
+
+    // a loop already at the kept endpoint stays, so it may be required
+    let loop_id = ps.add_candidate(Edge { u: 0, v: 0 }).unwrap();
+    ps.set_state(loop_id, CandidateState::Required).unwrap();
+    let c = ps.contract(2).unwrap();
+    assert_eq!(c.candidates[loop_id], Some(loop_id));
+    assert_eq!(ps.state(loop_id), Ok(CandidateState::Required));
+}
+
+#[test]
+fn requiring_a_blocked_candidate_fails() {
+    let (mut ps, id) = k5_minus_edge();
+    let missing = id(0, 1);
+    ps.set_state(missing, CandidateState::Forbidden).unwrap();
+    assert_eq!(
+        ps.set_state(missing, CandidateState::Required),
+        Err(Error::NotAddable { edge: missing })
+    );
+    assert_eq!(ps.state(missing), Ok(CandidateState::Forbidden));
+    assert!(!ps.query()[missing]);
+}
+
+#[test]
+fn required_candidates_guard_only_their_own_block() {
+    // K5 less (0, 1), with a triangle hanging off vertex 4, all selected
+    let mut edges = mk_edges(&edges_complete(5));
+    edges.extend([edge(4, 5), edge(5, 6), edge(4, 6)]);
+    let id = |u, v| edges.iter().position(|&e| e == edge(u, v)).unwrap();
+    let (missing, far, hanging) = (id(0, 1), id(3, 4), id(5, 6));
+    let added: Vec<bool> = (0..edges.len()).map(|i| i != missing).collect();
+    let mut ps = PlanarSubgraph::new(7, &edges, &added).unwrap();
+
+    // a required edge of the triangle holds nothing in the K5 back
+    ps.set_state(hanging, CandidateState::Required).unwrap();
+    ps.set(missing, true).unwrap();
+    ps.set(missing, false).unwrap();
+    ps.begin().unwrap();
+    ps.set(missing, true).unwrap();
+    ps.commit().unwrap();
+    ps.set(missing, false).unwrap();
+
+    // one in the block `missing` would close does
+    ps.set_state(far, CandidateState::Required).unwrap();
+    assert_eq!(
+        ps.set(missing, true),
+        Err(Error::NotAddable { edge: missing })
+    );
+    ps.begin().unwrap();
+    ps.set(missing, true).unwrap();
+    assert_eq!(ps.commit(), Err(Error::NotPlanar));
+    ps.rollback().unwrap();
+
+    // and once it is free again, the triangle's alone holds nothing back
+    ps.set_state(far, CandidateState::Free).unwrap();
+    ps.set(missing, true).unwrap();
+}
+
+#[test]
+fn required_candidates_keep_their_block_planar() {
+    let (mut ps, id) = k5_minus_edge();
+    let missing = id(0, 1);
+    // With nothing required, a blocked candidate may still be selected.
+    let mut loose = ps.fork().unwrap();
+    loose.set(missing, true).unwrap();
+
+    // The K5 is one block, so requiring any of its edges guards it.
+    ps.set_state(id(2, 3), CandidateState::Required).unwrap();
+    assert_eq!(
+        ps.set(missing, true),
+        Err(Error::NotAddable { edge: missing })
+    );
+
+    ps.begin().unwrap();
+    ps.set(missing, true).unwrap();
+    assert_eq!(ps.commit(), Err(Error::NotPlanar));
+    assert_eq!(ps.begin(), Err(Error::TransactionActive));
+    ps.set(id(2, 4), false).unwrap();
+    ps.commit().unwrap();
+    assert!(!ps.is_addable(id(2, 4)).unwrap());
+}
+
+/// Whether edges `a` and `b` of `edges` lie in one block, i.e. no vertex
+/// separates them once removed.
+fn same_block(n: usize, edges: &[Edge], a: usize, b: usize) -> bool {
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    // vertices and edge midpoints, joined everywhere but at `x`
+    (0..n).all(|x| {
+        let mut parent: Vec<usize> = (0..n + edges.len()).collect();
+        for (k, e) in edges.iter().enumerate() {
+            for w in [e.u, e.v] {
+                if w != x {
+                    let (p, q) = (find(&mut parent, n + k), find(&mut parent, w));
+                    parent[p] = q;
+                }
+            }
+        }
+        find(&mut parent, n + a) == find(&mut parent, n + b)
+    })
+}
+
+#[test]
+fn random_states_match_filtered_truth() {
+    let mut rng = common::rng(0x57A7);
+    let n = 12;
+    let edges = edges_random(n, 45, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected: HashSet<usize> = HashSet::new();
+    let mut forbidden: HashSet<usize> = HashSet::new();
+    let mut required: HashSet<usize> = HashSet::new();
+    // whether selecting `i` would put a required candidate in its block
+    let guarded = |selected: &HashSet<usize>, required: &HashSet<usize>, i: usize| {
+        let mut ids: Vec<usize> = selected.iter().copied().collect();
+        ids.push(i);
+        let with: Vec<Edge> = ids.iter().map(|&j| edges[j]).collect();
+        required.iter().any(|r| {
+            let at = ids.iter().position(|j| j == r).unwrap();
+            edges[*r].u != edges[*r].v && same_block(n, &with, at, ids.len() - 1)
+        })
+    };
+
+    for step in 0..120 {
+        let i = rng.random_range(0..edges.len());
+        let truth = truth_addability_mask(n, &selected, &edges);
+        match rng.random_range(0..4) {
+            0 => {
+                let state = *[
+                    CandidateState::Free,
+                    CandidateState::Required,
+                    CandidateState::Forbidden,
+                ]
+                .choose(&mut rng)
+                .unwrap();
+                let res = ps.set_state(i, state);
+                if state == CandidateState::Required && !selected.contains(&i) && !truth[i] {
+                    assert_eq!(res, Err(Error::NotAddable { edge: i }), "step {step}");
+                    continue;
+                }
+                res.unwrap();
+                forbidden.remove(&i);
+                required.remove(&i);
+                match state {
+                    CandidateState::Required => {
+                        required.insert(i);
+                        selected.insert(i);
+                    }
+                    CandidateState::Forbidden => {
+                        forbidden.insert(i);
+                        selected.remove(&i);
+                    }
+                    CandidateState::Free => {}
+                }
+            }
+            1 => {
+                let res = ps.set(i, false);
+                if required.contains(&i) {
+                    assert_eq!(res, Err(Error::Required { edge: i }), "step {step}");
+                } else {
+                    res.unwrap();
+                    selected.remove(&i);
+                }
+            }
+            _ => {
+                let res = ps.set(i, true);
+                if selected.contains(&i) {
+                    res.unwrap();
+                } else if forbidden.contains(&i) {
+                    assert_eq!(res, Err(Error::Forbidden { edge: i }), "step {step}");
+                } else if !truth[i] && guarded(&selected, &required, i) {
+                    assert_eq!(res, Err(Error::NotAddable { edge: i }), "step {step}");
+                } else if truth[i] {
+                    res.unwrap();
+                    selected.insert(i);
+                } else {
+                    // Keep the selection planar so the truth stays meaningful.
+                    res.unwrap();
+                    ps.set(i, false).unwrap();
+                }
+            }
+        }
+
+        let truth = truth_addability_mask(n, &selected, &edges);
+        let mask = ps.query();
+        for j in 0..edges.len() {
+            assert_eq!(
+                mask[j],
+                truth[j] && !forbidden.contains(&j),
+                "step {step}, edge {j}"
+            );
+        }
+    }
+}
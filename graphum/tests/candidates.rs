@@ -0,0 +1,105 @@
+mod common;
+
+use common::{edge, edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+#[test]
+fn growing_and_shrinking_matches_truth() {
+    let mut rng = common::rng(0xCA4D);
+    let n = 12;
+    let pool = edges_random(n, 45, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &[], &[]).unwrap();
+
+    // `edges[id]` mirrors the id space, removed candidates included.
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut live: HashSet<usize> = HashSet::new();
+    let mut selected: HashSet<usize> = HashSet::new();
+    let mut next = pool.iter().copied();
+
+    for step in 0..150 {
+        match rng.random_range(0..4) {
+            0 | 1 => {
+                let Some(e) = next.next() else { continue };
+                let id = ps.add_candidate(e).unwrap();
+                assert_eq!(id, edges.len());
+                edges.push(e);
+                live.insert(id);
+            }
+            2 => {
+                let ids: Vec<usize> = live.iter().copied().collect();
+                let Some(&id) = ids.choose(&mut rng) else {
+                    continue;
+                };
+                ps.remove_candidate(id).unwrap();
+                live.remove(&id);
+                selected.remove(&id);
+            }
+            _ => {
+                let mask = ps.query();
+                let legal: Vec<usize> = live.iter().copied().filter(|&i| mask[i]).collect();
+                let Some(&id) = legal.choose(&mut rng) else {
+                    continue;
+                };
+                ps.set(id, true).unwrap();
+                selected.insert(id);
+            }
+        }
+
+        let truth = truth_addability_mask(n, &selected, &edges);
+        let mask = ps.query();
+        assert_eq!(mask.len(), edges.len());
+        for id in 0..edges.len() {
+            let expect = live.contains(&id) && truth[id];
+            assert_eq!(mask[id], expect, "step {step}, edge {id}");
+        }
+    }
+}
+
+#[test]
+fn added_candidate_sees_existing_selection() {
+    let n = 5;
+    let k5 = mk_edges(&edges_complete(n));
+    let last = k5.len() - 1;
+    let mut ps = PlanarSubgraph::new(n, &k5[..last], &vec![true; last]).unwrap();
+    let id = ps.add_candidate(k5[last]).unwrap();
+    assert_eq!(id, last);
+    assert!(!ps.is_addable(id).unwrap());
+
+    // Dropping any selected edge of K5 minus an edge frees the new one.
+    ps.remove_candidate(0).unwrap();
+    assert!(ps.is_addable(id).unwrap());
+    assert!(!ps.query()[0]);
+}
+
+#[test]
+fn removed_ids_are_rejected_and_not_reused() {
+    let mut ps = PlanarSubgraph::new(3, &[edge(0, 1), edge(1, 2)], &[true, false]).unwrap();
+    ps.remove_candidate(0).unwrap();
+    assert!(!ps.is_candidate(0));
+    assert_eq!(ps.set(0, true), Err(Error::RemovedCandidate { edge: 0 }));
+    assert_eq!(ps.is_addable(0), Err(Error::RemovedCandidate { edge: 0 }));
+    assert_eq!(
+        ps.remove_candidate(0),
+        Err(Error::RemovedCandidate { edge: 0 })
+    );
+    assert_eq!(ps.add_candidate(edge(0, 2)), Ok(2));
+    assert_eq!(ps.num_edges(), 3);
+
+    assert_eq!(
+        ps.add_candidate(edge(1, 3)),
+        Err(Error::VertexOutOfRange {
+            edge: 3,
+            vertex: 3,
+            num_verts: 3
+        })
+    );
+    assert_eq!(ps.add_candidate(Edge { u: 2, v: 2 }), Ok(3));
+
+    ps.begin().unwrap();
+    assert_eq!(ps.add_candidate(edge(0, 1)), Err(Error::TransactionActive));
+    assert_eq!(ps.remove_candidate(1), Err(Error::TransactionActive));
+    ps.rollback().unwrap();
+}
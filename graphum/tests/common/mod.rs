@@ -100,7 +100,7 @@ pub fn greedily_fill_to_maximal_planar(
     for i in &selected {
         mask_init[*i] = true;
     }
-    let mut ps = PlanarSubgraph::new(n, edges_all, &mask_init);
+    let mut ps = PlanarSubgraph::new(n, edges_all, &mask_init).unwrap();
     let mut sel = selected;
     loop {
         let mask = mask_from_spqr(&ps);
@@ -111,7 +111,7 @@ pub fn greedily_fill_to_maximal_planar(
         let Some((i, _)) = next else {
             break;
         };
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
         sel.insert(i);
     }
     sel
@@ -0,0 +1,93 @@
+mod common;
+
+use common::edges_random;
+use rand::seq::IndexedRandom;
+use std::{collections::HashSet, sync::Arc, thread};
+
+use graphum::spqr::{PlanarSubgraph, PlanarSubgraphReader};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn handles_are_send_and_sync() {
+    assert_send_sync::<PlanarSubgraph>();
+    assert_send_sync::<PlanarSubgraphReader>();
+}
+
+#[test]
+fn readers_only_observe_published_states() {
+    let mut rng = common::rng(0x5EAD);
+    let n = 30;
+    let edges = edges_random(n, 120, &mut rng);
+
+    // Replay the writer's updates up front to know every state it passes.
+    let mut script = Vec::new();
+    let mut states = HashSet::new();
+    {
+        let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+        states.insert(ps.query());
+        loop {
+            let mask = ps.query();
+            let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+            let Some(&i) = legal.choose(&mut rng) else {
+                break;
+            };
+            ps.set(i, true).unwrap();
+            script.push(i);
+            states.insert(ps.query());
+        }
+    }
+    let states = Arc::new(states);
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let reader = ps.reader();
+            let states = Arc::clone(&states);
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    let mask = reader.query();
+                    assert!(states.contains(&mask));
+                }
+            })
+        })
+        .collect();
+    for &i in &script {
+        ps.set(i, true).unwrap();
+    }
+    for r in readers {
+        r.join().unwrap();
+    }
+}
+
+#[test]
+fn shared_references_query_in_parallel() {
+    let mut rng = common::rng(0x5A4E);
+    let n = 20;
+    let edges = edges_random(n, 70, &mut rng);
+    let ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let expect = ps.fork().unwrap().query();
+    let m = edges.len();
+    thread::scope(|s| {
+        for t in 0..4 {
+            let (ps, expect) = (&ps, &expect);
+            s.spawn(move || {
+                for i in (t..m).step_by(4) {
+                    assert_eq!(ps.is_addable(i).unwrap(), expect[i]);
+                }
+                assert_eq!(ps.query(), *expect);
+            });
+        }
+    });
+}
+
+#[test]
+fn reader_outlives_writer() {
+    let edges = edges_random(6, 10, &mut common::rng(7));
+    let ps = PlanarSubgraph::new(6, &edges, &vec![false; edges.len()]).unwrap();
+    let reader = ps.reader();
+    let mask = ps.query();
+    drop(ps);
+    assert_eq!(reader.query(), mask);
+    assert_eq!(reader.num_edges(), edges.len());
+}
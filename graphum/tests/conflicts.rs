@@ -0,0 +1,70 @@
+mod common;
+
+use common::{edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+
+use graphum::{Edge, spqr::PlanarSubgraph};
+
+fn truth_conflicts(n: usize, selected: &HashSet<usize>, edges: &[Edge], i: usize) -> Vec<usize> {
+    let before = truth_addability_mask(n, selected, edges);
+    if !before[i] {
+        return Vec::new();
+    }
+    let mut with = selected.clone();
+    with.insert(i);
+    let after = truth_addability_mask(n, &with, edges);
+    (0..edges.len())
+        .filter(|&j| j != i && before[j] && !after[j])
+        .collect()
+}
+
+#[test]
+fn conflicts_match_truth_and_leave_state_alone() {
+    let mut rng = common::rng(0xC0F1);
+    let n = 12;
+    let edges = edges_random(n, 40, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+
+    for _ in 0..15 {
+        let mask = ps.query();
+        for i in 0..edges.len() {
+            assert_eq!(
+                ps.conflicts(i).unwrap(),
+                truth_conflicts(n, &selected, &edges, i),
+                "edge {i}"
+            );
+        }
+        assert_eq!(ps.query(), mask);
+
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let Some(&i) = legal.choose(&mut rng) else {
+            break;
+        };
+        ps.set(i, true).unwrap();
+        selected.insert(i);
+    }
+}
+
+#[test]
+fn conflict_graph_is_symmetric_and_matches_single_queries() {
+    let n = 7;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    for i in [0, 1, 2, 3, 6, 7, 11] {
+        ps.set(i, true).unwrap();
+    }
+    ps.query();
+    let evaluations = ps.evaluations();
+    let graph = ps.conflict_graph();
+    // speculating leaves every settled answer in place
+    assert_eq!(ps.evaluations(), evaluations);
+    assert_eq!(graph.len(), edges.len());
+    for (i, nbrs) in graph.iter().enumerate() {
+        assert_eq!(*nbrs, ps.conflicts(i).unwrap());
+        for &j in nbrs {
+            assert!(graph[j].contains(&i), "{i} -> {j} not mirrored");
+        }
+    }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{edge, edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+#[test]
+fn contraction_merges_parallels_and_drops_loops() {
+    // Triangle 0-1-2 with two unselected spokes to 3 and a loop at 1.
+    let edges = [
+        edge(0, 1),
+        edge(1, 2),
+        edge(0, 2),
+        edge(1, 3),
+        edge(0, 3),
+        Edge { u: 1, v: 1 },
+    ];
+    let mut ps = PlanarSubgraph::new(4, &edges, &[true, true, false, false, false, false]).unwrap();
+
+    let c = ps.contract(0).unwrap();
+    assert_eq!(c.vertex, 0);
+    assert_eq!(c.vertices, vec![0, 0, 2, 3]);
+    assert_eq!(
+        c.candidates,
+        vec![None, Some(1), Some(1), Some(3), Some(3), None]
+    );
+
+    assert!(!ps.is_vertex(1));
+    assert_eq!(ps.edges()[1], edge(0, 2));
+    assert_eq!(ps.edges()[3], edge(0, 3));
+    let live: Vec<usize> = (0..edges.len()).filter(|&i| ps.is_candidate(i)).collect();
+    assert_eq!(live, vec![1, 3]);
+    // Edge 1 stays selected, so the spoke is the only thing left to add.
+    assert_eq!(ps.query(), vec![false, false, false, true, false, false]);
+    assert_eq!(ps.can_add(0, 2), Ok(true));
+}
+
+#[test]
+fn contraction_leaves_existing_parallels_and_loops_alone() {
+    // Edge 0-1 with a parallel pair and a loop at 0 already, and a spoke
+    // from each endpoint to 3.
+    let edges = [
+        edge(0, 1),
+        edge(0, 2),
+        edge(0, 2),
+        Edge { u: 0, v: 0 },
+        edge(1, 3),
+        edge(0, 3),
+    ];
+    let mut ps = PlanarSubgraph::new(4, &edges, &[true, true, true, true, false, false]).unwrap();
+
+    // Only the spokes, which the contraction makes parallel, are merged;
+    // the rest stay selected, and so not addable.
+    let c = ps.contract(0).unwrap();
+    assert_eq!(
+        c.candidates,
+        vec![None, Some(1), Some(2), Some(3), Some(4), Some(4)]
+    );
+    let live: Vec<usize> = (0..edges.len()).filter(|&i| ps.is_candidate(i)).collect();
+    assert_eq!(live, vec![1, 2, 3, 4]);
+    assert_eq!(ps.edge(4), Ok(edge(0, 3)));
+    assert_eq!(ps.query(), vec![false, false, false, false, true, false]);
+}
+
+#[test]
+fn merged_group_is_selected_if_any_member_was() {
+    let edges = [edge(0, 1), edge(0, 2), edge(1, 2)];
+    let mut ps = PlanarSubgraph::new(3, &edges, &[true, false, true]).unwrap();
+    let c = ps.contract(0).unwrap();
+    assert_eq!(c.candidates, vec![None, Some(1), Some(1)]);
+    assert!(!ps.is_addable(1).unwrap());
+    ps.set(1, false).unwrap();
+    assert!(ps.is_addable(1).unwrap());
+}
+
+#[test]
+fn contracting_frees_blocked_candidates() {
+    let n = 5;
+    let edges = mk_edges(&edges_complete(n));
+    let missing = edges.iter().position(|&e| e == edge(0, 1)).unwrap();
+    let added: Vec<bool> = (0..edges.len()).map(|i| i != missing).collect();
+    let mut ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+    assert!(!ps.is_addable(missing).unwrap());
+
+    let e23 = edges.iter().position(|&e| e == edge(2, 3)).unwrap();
+    let c = ps.contract(e23).unwrap();
+    assert_eq!(c.candidates[missing], Some(missing));
+    assert!(ps.is_addable(missing).unwrap());
+}
+
+#[test]
+fn repeated_contractions_match_truth() {
+    let mut rng = common::rng(0xC047);
+    let n = 16;
+    let mut edges = edges_random(n, 60, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let order: Vec<usize> = (0..edges.len()).step_by(2).collect();
+    let mut selected: HashSet<usize> = ps.fill_greedy(&order).unwrap().into_iter().collect();
+    let mut live: HashSet<usize> = (0..edges.len()).collect();
+
+    for step in 0..10 {
+        let sel: Vec<usize> = selected.iter().copied().collect();
+        let Some(&e) = sel.choose(&mut rng) else {
+            break;
+        };
+        let c = ps.contract(e).unwrap();
+
+        let next: HashSet<usize> = selected.iter().filter_map(|&i| c.candidates[i]).collect();
+        for (i, to) in c.candidates.iter().enumerate() {
+            if to.is_none() {
+                live.remove(&i);
+            }
+        }
+        for &i in &live {
+            let Edge { u, v } = edges[i];
+            edges[i] = edge(c.vertices[u], c.vertices[v]);
+        }
+        selected = next;
+        assert_eq!(ps.edges().len(), edges.len());
+        for &i in &live {
+            assert_eq!(edge(ps.edges()[i].u, ps.edges()[i].v), edges[i]);
+        }
+
+        let truth = truth_addability_mask(n, &selected, &edges);
+        let mask = ps.query();
+        for i in 0..edges.len() {
+            assert_eq!(
+                mask[i],
+                live.contains(&i) && truth[i],
+                "step {step}, edge {i}"
+            );
+        }
+    }
+}
+
+#[test]
+fn contract_rejects_unselected_edges() {
+    let edges = [edge(0, 1), edge(1, 2)];
+    let mut ps = PlanarSubgraph::new(3, &edges, &[true, false]).unwrap();
+    assert_eq!(ps.contract(1), Err(Error::NotSelected { edge: 1 }));
+    assert_eq!(ps.query(), vec![false, true]);
+
+    ps.begin().unwrap();
+    assert_eq!(ps.contract(0), Err(Error::TransactionActive));
+    ps.rollback().unwrap();
+}
@@ -9,7 +9,7 @@ fn query_returns_list_and_reflects_updates() {
     let n = 10;
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mv = ps.query();
     assert_eq!(mv.len(), edges.len());
@@ -22,18 +22,18 @@ fn query_returns_list_and_reflects_updates() {
         .expect("expected at least one addable index");
     let before = mv[i];
     assert!(before);
-    ps.set(i, true);
+    ps.set(i, true).unwrap();
 
     assert_eq!(mv[i], before);
     let mv_after_add = ps.query();
     assert!(!mv_after_add[i]);
 
-    ps.set(i, true);
+    ps.set(i, true).unwrap();
     assert_eq!(mv[i], before);
     let mv_after_readd = ps.query();
     assert!(!mv_after_readd[i]);
 
-    ps.set(i, false);
+    ps.set(i, false).unwrap();
     assert_eq!(mv[i], before);
     let mv_after_remove = ps.query();
     assert!(mv_after_remove[i]);
@@ -44,13 +44,13 @@ fn selected_candidates_always_zero_in_mask() {
     let n = 9;
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mv = mask_from_spqr(&ps);
     let mut chosen = Vec::new();
     for (idx, b) in mv.iter().enumerate() {
         if *b {
-            ps.set(idx, true);
+            ps.set(idx, true).unwrap();
             chosen.push(idx);
         }
         if chosen.len() >= 5 {
@@ -14,7 +14,7 @@ fn k5_minus_one_edge_blocked() {
     let n = 5;
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let idx = pair_index(&edges);
     let missing = (0, 1);
@@ -23,7 +23,7 @@ fn k5_minus_one_edge_blocked() {
             if (u, v) == missing {
                 continue;
             }
-            ps.set(idx[&edge(u, v)], true);
+            ps.set(idx[&edge(u, v)], true).unwrap();
         }
     }
 
@@ -46,7 +46,7 @@ fn k33_minus_one_edge_blocked() {
     let b = [3, 4, 5];
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let idx = pair_index(&edges);
     let missing = (1, 3);
@@ -56,7 +56,7 @@ fn k33_minus_one_edge_blocked() {
                 continue;
             }
             let (uu, vv) = if u < v { (u, v) } else { (v, u) };
-            ps.set(idx[&edge(uu, vv)], true);
+            ps.set(idx[&edge(uu, vv)], true).unwrap();
         }
     }
 
@@ -78,20 +78,20 @@ fn wheel_graph_is_maximal_planar() {
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
     let idx = pair_index(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mut selected: HashSet<usize> = HashSet::new();
     for i in 1..(n - 1) {
         let eidx = idx[&edge(i, i + 1)];
-        ps.set(eidx, true);
+        ps.set(eidx, true).unwrap();
         selected.insert(eidx);
     }
     let eidx = idx[&edge(1, n - 1)];
-    ps.set(eidx, true);
+    ps.set(eidx, true).unwrap();
     selected.insert(eidx);
     for v in 1..n {
         let eidx = idx[&edge(0, v)];
-        ps.set(eidx, true);
+        ps.set(eidx, true).unwrap();
         selected.insert(eidx);
     }
 
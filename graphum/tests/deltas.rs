@@ -0,0 +1,194 @@
+mod common;
+
+use common::{edges_complete, edges_random, mk_edges};
+use rand::seq::IndexedRandom;
+use std::sync::{Arc, Mutex, mpsc};
+
+use graphum::{
+    Edge, Error,
+    spqr::{CandidateState, PlanarSubgraph},
+};
+
+fn diff(a: &[bool], b: &[bool]) -> Vec<usize> {
+    (0..a.len()).filter(|&i| a[i] != b[i]).collect()
+}
+
+#[test]
+fn set_and_diff_matches_mask_diff() {
+    let mut rng = common::rng(0xD17F);
+    let n = 18;
+    let edges = edges_random(n, 70, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = Vec::new();
+
+    for step in 0..80 {
+        let before = ps.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| before[i]).collect();
+        let (i, present) = match legal.choose(&mut rng) {
+            Some(&i) if step % 4 != 3 => (i, true),
+            _ => match selected.choose(&mut rng) {
+                Some(&i) => (i, false),
+                None => break,
+            },
+        };
+        let flipped = ps.set_and_diff(i, present).unwrap();
+        if present {
+            selected.push(i);
+        } else {
+            selected.retain(|&j| j != i);
+        }
+        assert_eq!(flipped, diff(&before, &ps.query()), "step {step}");
+    }
+}
+
+#[test]
+fn subscribers_see_set_and_commit_deltas() {
+    let n = 6;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let rx = ps.subscribe();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    ps.on_change(move |d| sink.lock().unwrap().push(d.to_vec()));
+
+    let mut expect = Vec::new();
+    for i in [0, 1, 5] {
+        expect.push(ps.set_and_diff(i, true).unwrap());
+    }
+
+    ps.begin().unwrap();
+    for i in [2, 3, 4, 6, 9] {
+        ps.set(i, true).unwrap();
+    }
+    assert_eq!(ps.set_and_diff(7, true), Err(Error::TransactionActive));
+    expect.push(ps.commit_and_diff().unwrap());
+
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), expect);
+    assert_eq!(*seen.lock().unwrap(), expect);
+
+    // Plain `set` and `commit` stay lazy and notify nobody.
+    let before = ps.query();
+    ps.set(0, false).unwrap();
+    ps.begin().unwrap();
+    ps.set(1, false).unwrap();
+    ps.commit().unwrap();
+    assert_ne!(ps.query(), before);
+    assert_eq!(rx.try_iter().count(), 0);
+    assert_eq!(seen.lock().unwrap().len(), expect.len());
+
+    // A dropped receiver unsubscribes without disturbing the others.
+    drop(rx);
+    ps.set_and_diff(0, true).unwrap();
+    assert_eq!(seen.lock().unwrap().len(), expect.len() + 1);
+}
+
+#[test]
+fn commit_and_diff_reports_net_change() {
+    let edges = mk_edges(&edges_complete(5));
+    let mut ps = PlanarSubgraph::new(5, &edges, &vec![false; edges.len()]).unwrap();
+    ps.begin().unwrap();
+    ps.set(0, true).unwrap();
+    ps.set(0, false).unwrap();
+    assert_eq!(ps.commit_and_diff(), Ok(vec![]));
+    assert_eq!(ps.commit_and_diff(), Err(Error::NoTransaction));
+}
+
+/// K5 with all but its last `open` edges selected, subscribed to, and a
+/// mirror of its addability mask kept from the published deltas alone.
+struct Mirror {
+    ps: PlanarSubgraph,
+    edges: Vec<Edge>,
+    rx: mpsc::Receiver<Vec<usize>>,
+    mask: Vec<bool>,
+}
+
+impl Mirror {
+    fn k5(open: usize) -> Self {
+        let edges = mk_edges(&edges_complete(5));
+        let init: Vec<bool> = (0..edges.len()).map(|i| i + open < edges.len()).collect();
+        let mut ps = PlanarSubgraph::new(5, &edges, &init).unwrap();
+        let rx = ps.subscribe();
+        let mask = ps.query();
+        Mirror {
+            ps,
+            edges,
+            rx,
+            mask,
+        }
+    }
+
+    fn last(&self) -> usize {
+        self.edges.len() - 1
+    }
+
+    /// Replay the deltas published since the last call, and check the
+    /// mirror against a fresh query.
+    fn sync(&mut self) -> &[bool] {
+        for d in self.rx.try_iter() {
+            for j in d {
+                self.mask[j] = !self.mask[j];
+            }
+        }
+        assert_eq!(self.mask, self.ps.query());
+        &self.mask
+    }
+}
+
+#[test]
+fn subscribers_see_removed_candidates() {
+    // the last edge is not addable until a selected one goes
+    let mut m = Mirror::k5(1);
+    let last = m.last();
+    assert!(!m.sync()[last]);
+
+    // a selected candidate, which frees the last one, then the last one
+    m.ps.remove_candidate(0).unwrap();
+    assert!(m.sync()[last]);
+    m.ps.remove_candidate(last).unwrap();
+    m.sync();
+}
+
+#[test]
+fn subscribers_see_removed_vertices() {
+    let mut m = Mirror::k5(1);
+    let last = m.last();
+    let Edge { u, v } = m.edges[last];
+
+    // what is left is a K4 and the last edge, which is addable again
+    let x = (0..5).find(|&x| x != u && x != v).unwrap();
+    m.ps.remove_vertex(x).unwrap();
+    assert!(m.sync()[last]);
+}
+
+#[test]
+fn subscribers_see_contractions() {
+    let mut m = Mirror::k5(1);
+    let last = m.last();
+    let Edge { u, v } = m.edges[last];
+
+    // contracting any edge away from the last one leaves a K4 less that edge
+    let e = (0..last)
+        .find(|&i| {
+            [m.edges[i].u, m.edges[i].v]
+                .iter()
+                .all(|&x| x != u && x != v)
+        })
+        .unwrap();
+    m.ps.contract(e).unwrap();
+    assert!(m.sync()[last]);
+}
+
+#[test]
+fn subscribers_see_state_changes() {
+    let mut m = Mirror::k5(2);
+    let last = m.last();
+
+    // requiring one of the two open edges blocks the other, forbidding a
+    // selected edge frees it again, and freeing that one makes it addable
+    m.ps.set_state(last - 1, CandidateState::Required).unwrap();
+    m.sync();
+    m.ps.set_state(0, CandidateState::Forbidden).unwrap();
+    m.sync();
+    m.ps.set_state(0, CandidateState::Free).unwrap();
+    m.sync();
+}
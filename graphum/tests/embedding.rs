@@ -0,0 +1,124 @@
+mod common;
+
+use common::{cycle_edges, edge, edges_complete, grid_edges, mk_edges};
+use std::collections::HashSet;
+
+use graphum::{mps::planar_embedding, spqr::PlanarSubgraph};
+
+fn assert_euler(n: usize, edges: &[graphum::Edge], components: usize) {
+    let emb = planar_embedding(n, edges)
+        .unwrap()
+        .expect("graph should be planar");
+    assert_eq!(emb.num_verts(), n);
+    for v in 0..n {
+        let deg = edges
+            .iter()
+            .map(|e| (e.u == v) as usize + (e.v == v) as usize)
+            .sum::<usize>();
+        assert_eq!(emb.rotation(v).len(), deg, "rotation size at {v}");
+    }
+    let faces = emb.faces();
+    let darts: usize = faces.iter().map(Vec::len).sum();
+    assert_eq!(darts, 2 * edges.len());
+    // Each component's walks are counted separately, including its outer face.
+    let isolated = (0..n).filter(|&v| emb.rotation(v).is_empty()).count();
+    assert_eq!(
+        n + faces.len(),
+        edges.len() + 2 * (components - isolated) + isolated,
+        "Euler's formula violated"
+    );
+}
+
+#[test]
+fn cycle_has_two_faces() {
+    let n = 7;
+    let edges = cycle_edges(n);
+    let emb = planar_embedding(n, &edges).unwrap().unwrap();
+    let faces = emb.faces();
+    assert_eq!(faces.len(), 2);
+    for f in faces {
+        assert_eq!(f.len(), n);
+    }
+}
+
+#[test]
+fn k4_and_grid_satisfy_euler() {
+    assert_euler(4, &edges_complete(4), 1);
+    assert_euler(20, &grid_edges(5, 4), 1);
+}
+
+#[test]
+fn disconnected_graph_with_isolated_vertex() {
+    let mut edges = cycle_edges(3);
+    edges.push(edge(3, 4));
+    edges.push(edge(4, 5));
+    assert_euler(7, &edges, 3);
+}
+
+#[test]
+fn parallel_edges_and_loops_are_embedded() {
+    let edges = vec![
+        edge(0, 1),
+        edge(1, 2),
+        edge(0, 2),
+        edge(0, 1),
+        edge(0, 1),
+        graphum::Edge { u: 2, v: 2 },
+    ];
+    assert_euler(3, &edges, 1);
+    let emb = planar_embedding(3, &edges).unwrap().unwrap();
+    let rot = emb.rotation(2);
+    let pos = rot.iter().position(|&e| e == 5).unwrap();
+    assert_eq!(rot[(pos + 1) % rot.len()], 5);
+}
+
+#[test]
+fn non_planar_graphs_return_witness() {
+    let k5 = edges_complete(5);
+    let witness = planar_embedding(5, &k5).unwrap().unwrap_err();
+    assert_eq!(witness.edges.len(), k5.len());
+
+    let k33: Vec<_> = [0, 1, 2]
+        .iter()
+        .flat_map(|&u| [3, 4, 5].map(|v| edge(u, v)))
+        .collect();
+    let witness = planar_embedding(6, &k33).unwrap().unwrap_err();
+    assert_eq!(witness.edges.len(), k33.len());
+}
+
+#[test]
+fn maximal_selection_embeds_as_triangulation() {
+    let n = 9;
+    let edges = mk_edges(&edges_complete(n));
+    let selected = common::greedily_fill_to_maximal_planar(n, &edges, HashSet::new());
+    let mut mask = vec![false; edges.len()];
+    for &i in &selected {
+        mask[i] = true;
+    }
+    let ps = PlanarSubgraph::new(n, &edges, &mask).unwrap();
+
+    let emb = ps.embedding().unwrap();
+    assert_eq!(emb.edges(), &edges[..]);
+    let mut seen: Vec<usize> = (0..n).flat_map(|v| emb.rotation(v).to_vec()).collect();
+    seen.sort();
+    let mut expect: Vec<usize> = selected.iter().flat_map(|&i| [i, i]).collect();
+    expect.sort();
+    assert_eq!(seen, expect);
+
+    let faces = emb.faces();
+    assert_eq!(faces.len(), 2 * n - 4);
+    assert!(faces.iter().all(|f| f.len() == 3));
+}
+
+#[test]
+fn selection_embedding_tracks_updates() {
+    let edges = grid_edges(3, 2);
+    let mut ps = PlanarSubgraph::new(6, &edges, &vec![false; edges.len()]).unwrap();
+    assert!((0..6).all(|v| ps.embedding().unwrap().rotation(v).is_empty()));
+    for i in 0..edges.len() {
+        ps.set(i, true).unwrap();
+    }
+    assert_eq!(ps.embedding().unwrap().faces().len(), 3);
+    ps.set(0, false).unwrap();
+    assert_eq!(ps.embedding().unwrap().faces().len(), 2);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{edge, edges_complete, mk_edges};
+
+use graphum::{Error, mps, spqr::PlanarSubgraph};
+
+#[test]
+fn out_of_range_endpoint_is_reported_for_every_entry_point() {
+    let mut edges = edges_complete(4);
+    edges.push(edge(2, 9));
+    let expected = Error::VertexOutOfRange {
+        edge: 6,
+        vertex: 9,
+        num_verts: 4,
+    };
+
+    assert_eq!(mps::boyer_myrvold_witness(4, &edges), Err(expected));
+    assert_eq!(mps::boyer_myrvold_witness_ids(4, &edges), Err(expected));
+    assert_eq!(mps::kuratowski_subdivision(4, &edges), Err(expected));
+    assert_eq!(
+        mps::kuratowski_witnesses(4, &edges, &Default::default()),
+        Err(expected)
+    );
+    assert_eq!(mps::planar_embedding(4, &edges).err(), Some(expected));
+    assert_eq!(
+        PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).err(),
+        Some(expected)
+    );
+}
+
+#[test]
+fn planar_subgraph_rejects_malformed_construction() {
+    let edges = mk_edges(&edges_complete(4));
+    assert_eq!(
+        PlanarSubgraph::new(4, &edges, &[false; 3]).err(),
+        Some(Error::LengthMismatch { edges: 6, mask: 3 })
+    );
+}
+
+#[test]
+fn bad_edge_id_leaves_instance_usable() {
+    let edges = mk_edges(&edges_complete(4));
+    let mut ps = PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).unwrap();
+    assert_eq!(
+        ps.set(6, true),
+        Err(Error::EdgeOutOfRange {
+            edge: 6,
+            num_edges: 6
+        })
+    );
+    ps.set(0, true).unwrap();
+    assert!(!ps.query()[0]);
+    assert!(ps.query()[1..].iter().all(|b| *b));
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::{edge, edges_complete, edges_random, greedily_fill_to_maximal_planar, mk_edges};
+use rand::{RngExt, seq::SliceRandom};
+use std::collections::HashSet;
+
+use graphum::{Error, spqr::PlanarSubgraph};
+
+#[test]
+fn fill_in_id_order_matches_query_loop() {
+    let mut rng = common::rng(0xF111);
+    for n in [8, 14, 20] {
+        let edges = edges_random(n, 4 * n, &mut rng);
+        let start: HashSet<usize> = (0..edges.len()).filter(|i| i % 7 == 0).take(3).collect();
+        let expect = greedily_fill_to_maximal_planar(n, &edges, start.clone());
+
+        let mut added = vec![false; edges.len()];
+        for &i in &start {
+            added[i] = true;
+        }
+        let mut ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+        let order: Vec<usize> = (0..edges.len()).collect();
+        let selected = ps.fill_greedy(&order).unwrap();
+
+        assert!(selected.windows(2).all(|w| w[0] < w[1]));
+        let mut got: HashSet<usize> = selected.into_iter().collect();
+        assert!(got.is_disjoint(&start));
+        got.extend(&start);
+        assert_eq!(got, expect, "n = {n}");
+        assert!(ps.query().iter().all(|b| !b));
+    }
+}
+
+#[test]
+fn fill_follows_the_given_order() {
+    let mut rng = common::rng(0xF112);
+    let n = 12;
+    let edges = edges_random(n, 50, &mut rng);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.shuffle(&mut rng);
+
+    let mut reference = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut expect = Vec::new();
+    for &i in &order {
+        if reference.is_addable(i).unwrap() {
+            reference.set(i, true).unwrap();
+            expect.push(i);
+        }
+    }
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    assert_eq!(ps.fill_greedy(&order).unwrap(), expect);
+    assert_eq!(ps.query(), reference.query());
+
+    // A second pass finds nothing left to add.
+    assert!(ps.fill_greedy(&order).unwrap().is_empty());
+}
+
+#[test]
+fn weighted_fill_prefers_heavier_candidates() {
+    let mut rng = common::rng(0xF113);
+    let n = 10;
+    let edges = edges_random(n, 35, &mut rng);
+    let mut priorities: Vec<f64> = (0..edges.len())
+        .map(|_| rng.random_range(0..5) as f64)
+        .collect();
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    ps.remove_candidate(0).unwrap();
+    priorities[0] = f64::INFINITY;
+
+    let mut order: Vec<usize> = (1..edges.len()).collect();
+    order.sort_by(|&a, &b| priorities[b].total_cmp(&priorities[a]));
+    let mut reference = ps.fork().unwrap();
+    let expect = reference.fill_greedy(&order).unwrap();
+
+    assert_eq!(ps.fill_greedy_weighted(&priorities).unwrap(), expect);
+    assert_eq!(
+        ps.fill_greedy_weighted(&priorities[1..]),
+        Err(Error::LengthMismatch {
+            edges: edges.len(),
+            mask: edges.len() - 1
+        })
+    );
+}
+
+#[test]
+fn rejected_fill_selects_nothing() {
+    let n = 5;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+
+    assert_eq!(
+        ps.fill_greedy(&[0, 1, 10]),
+        Err(Error::EdgeOutOfRange {
+            edge: 10,
+            num_edges: 10
+        })
+    );
+    assert!(ps.query().iter().all(|&b| b));
+
+    ps.begin().unwrap();
+    assert_eq!(ps.fill_greedy(&[0]), Err(Error::TransactionActive));
+    ps.rollback().unwrap();
+}
+
+#[test]
+fn fill_notifies_subscribers_once() {
+    let n = 6;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let rx = ps.subscribe();
+
+    let order: Vec<usize> = (0..edges.len()).collect();
+    let selected = ps.fill_greedy(&order).unwrap();
+    assert_eq!(selected.len(), 3 * n - 6);
+
+    // Every candidate started addable and none is now.
+    assert_eq!(rx.try_recv().unwrap(), order);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn fill_matches_a_set_loop_from_a_nonplanar_start() {
+    let mut rng = common::rng(0xF114);
+    let n = 24;
+    let mut edges = mk_edges(&edges_complete(5));
+    let k5 = edges.len();
+    edges.extend(edges_random(n, 120, &mut rng));
+    let init: Vec<bool> = (0..edges.len()).map(|i| i < k5).collect();
+    let mut order: Vec<usize> = (k5..edges.len()).collect();
+    order.shuffle(&mut rng);
+    order.extend_from_within(..20);
+
+    let mut reference = PlanarSubgraph::new(n, &edges, &init).unwrap();
+    let mut expect = Vec::new();
+    for &i in &order {
+        if reference.is_addable(i).unwrap() {
+            reference.set(i, true).unwrap();
+            expect.push(i);
+        }
+    }
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &init).unwrap();
+    assert_eq!(ps.fill_greedy(&order).unwrap(), expect);
+    assert_eq!(ps.query(), reference.query());
+}
+
+#[test]
+fn subscribed_fill_leaves_other_components_alone() {
+    // two hexagons; the first has crossing chords, the second one chord
+    let mut edges: Vec<_> = (0..6)
+        .flat_map(|i| [edge(i, (i + 1) % 6), edge(6 + i, 6 + (i + 1) % 6)])
+        .collect();
+    let selected = edges.len();
+    edges.extend([edge(0, 3), edge(1, 4), edge(6, 9)]);
+    let (a1, a2, b) = (selected, selected + 1, selected + 2);
+    let init: Vec<bool> = (0..edges.len()).map(|i| i < selected).collect();
+    let mut ps = PlanarSubgraph::new(12, &edges, &init).unwrap();
+    let rx = ps.subscribe();
+
+    assert_eq!(ps.fill_greedy(&[a1, a2]).unwrap(), vec![a1]);
+    assert_eq!(rx.try_recv().unwrap(), vec![a1, a2]);
+    let before = ps.evaluations();
+    assert!(ps.is_addable(b).unwrap());
+    assert_eq!(ps.evaluations(), before + 1);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{edge, edges_random, truth_addability_mask};
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+
+use graphum::{Error, spqr::PlanarSubgraph};
+
+#[test]
+fn forks_evolve_independently() {
+    let mut rng = common::rng(0xF0F0);
+    let n = 14;
+    let edges = edges_random(n, 55, &mut rng);
+    let mut root = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut root_sel = HashSet::new();
+    for _ in 0..10 {
+        let mask = root.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let &i = legal.choose(&mut rng).unwrap();
+        root.set(i, true).unwrap();
+        root_sel.insert(i);
+    }
+    let root_mask = root.query();
+
+    let mut branches: Vec<(PlanarSubgraph, HashSet<usize>)> = (0..4)
+        .map(|_| (root.fork().unwrap(), root_sel.clone()))
+        .collect();
+    for (ps, sel) in &mut branches {
+        assert_eq!(ps.query(), root_mask);
+        for _ in 0..8 {
+            let mask = ps.query();
+            let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+            let Some(&i) = legal.choose(&mut rng) else {
+                break;
+            };
+            ps.set(i, true).unwrap();
+            sel.insert(i);
+        }
+        let &j = sel.iter().next().unwrap();
+        ps.set(j, false).unwrap();
+        sel.remove(&j);
+    }
+
+    assert_eq!(root.query(), root_mask);
+    for (ps, sel) in &branches {
+        assert_eq!(ps.query(), truth_addability_mask(n, sel, &edges));
+    }
+}
+
+#[test]
+fn fork_of_fork_and_pending_updates() {
+    let edges = vec![edge(0, 1), edge(1, 2), edge(0, 2), edge(2, 3)];
+    let mut ps = PlanarSubgraph::new(4, &edges, &[false; 4]).unwrap();
+    // Fork before anything was evaluated, and again with an update pending.
+    let a = ps.fork().unwrap();
+    ps.set(0, true).unwrap();
+    let mut b = ps.fork().unwrap();
+    b.set(1, true).unwrap();
+    let c = b.fork().unwrap();
+
+    assert_eq!(a.query(), vec![true; 4]);
+    assert_eq!(ps.query(), vec![false, true, true, true]);
+    assert_eq!(b.query(), vec![false, false, true, true]);
+    assert_eq!(c.query(), b.query());
+}
+
+#[test]
+fn candidates_added_to_a_fork_stay_there() {
+    let edges = vec![edge(0, 1), edge(1, 2)];
+    let mut ps = PlanarSubgraph::new(3, &edges, &[true, false]).unwrap();
+    let mut f = ps.fork().unwrap();
+    assert_eq!(f.add_candidate(edge(0, 2)), Ok(2));
+    assert_eq!(f.num_edges(), 3);
+    assert_eq!(ps.num_edges(), 2);
+    assert_eq!(ps.add_candidate(edge(0, 2)), Ok(2));
+    f.remove_candidate(0).unwrap();
+    assert!(ps.is_candidate(0));
+    assert_eq!(ps.query(), vec![false, true, true]);
+    assert_eq!(f.query(), vec![false, true, true]);
+
+    ps.begin().unwrap();
+    assert!(matches!(ps.fork(), Err(Error::TransactionActive)));
+}
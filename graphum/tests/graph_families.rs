@@ -31,12 +31,12 @@ fn truth_mask_on_cycle_matches_spqr() {
         let edges = edges_complete(n);
         let idx = pair_index(&edges);
         let edges_all = mk_edges(&edges);
-        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
         let e = cycle_edges(n);
         let selected: HashSet<usize> = e.iter().map(|edge| idx[edge]).collect();
         for i in &selected {
-            ps.set(*i, true);
+            ps.set(*i, true).unwrap();
         }
 
         let mask = mask_from_spqr(&ps);
@@ -58,12 +58,12 @@ fn truth_mask_on_grid_matches_spqr() {
     let edges = edges_complete(n);
     let idx = pair_index(&edges);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let e = grid_edges(w, h);
     let selected: HashSet<usize> = e.iter().map(|edge| idx[edge]).collect();
     for i in &selected {
-        ps.set(*i, true);
+        ps.set(*i, true).unwrap();
     }
 
     let mask = mask_from_spqr(&ps);
@@ -0,0 +1,162 @@
+mod common;
+
+use common::{edge, edges_random, mask_from_spqr, truth_addability_mask};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::spqr::PlanarSubgraph;
+
+/// Three dense clusters joined by a handful of bridging candidates, so that
+/// most updates leave the other clusters' components untouched.
+fn clustered_edges(rng: &mut rand::rngs::StdRng) -> (usize, Vec<graphum::Edge>) {
+    let k = 9;
+    let mut edges = Vec::new();
+    for c in 0..3 {
+        let off = c * k;
+        edges.extend(
+            edges_random(k, 26, rng)
+                .into_iter()
+                .map(|e| edge(e.u + off, e.v + off)),
+        );
+    }
+    edges.extend([edge(0, k), edge(k + 1, 2 * k), edge(2, 2 * k + 3)]);
+    (3 * k, edges)
+}
+
+#[test]
+fn toggles_in_one_cluster_keep_all_answers_exact() {
+    let mut rng = common::rng(0x1CE);
+    let (n, edges) = clustered_edges(&mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected: HashSet<usize> = HashSet::new();
+
+    for step in 0..150 {
+        let mask = mask_from_spqr(&ps);
+        let mut fresh_init = vec![false; edges.len()];
+        for &i in &selected {
+            fresh_init[i] = true;
+        }
+        let fresh = PlanarSubgraph::new(n, &edges, &fresh_init).unwrap();
+        assert_eq!(mask, mask_from_spqr(&fresh), "diverged at step {step}");
+        if step % 25 == 0 {
+            assert_eq!(mask, truth_addability_mask(n, &selected, &edges));
+        }
+
+        let addable: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let remove = selected.is_empty() || addable.is_empty() || rng.random_bool(0.35);
+        if remove {
+            let sel: Vec<usize> = selected.iter().copied().collect();
+            let Some(&i) = sel.choose(&mut rng) else {
+                break;
+            };
+            ps.set(i, false).unwrap();
+            selected.remove(&i);
+        } else {
+            let &i = addable.choose(&mut rng).unwrap();
+            ps.set(i, true).unwrap();
+            selected.insert(i);
+        }
+    }
+}
+
+#[test]
+fn toggling_an_edge_back_restores_the_mask() {
+    let mut rng = common::rng(0xB10C);
+    let (n, edges) = clustered_edges(&mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = Vec::new();
+    for i in 0..edges.len() {
+        if ps.query()[i] {
+            ps.set(i, true).unwrap();
+            selected.push(i);
+        }
+    }
+
+    let before = ps.query();
+    for &i in selected.iter().step_by(3) {
+        ps.set(i, false).unwrap();
+        assert!(ps.query()[i]);
+        ps.set(i, true).unwrap();
+        assert_eq!(ps.query(), before);
+    }
+}
+
+#[test]
+fn selecting_within_a_block_leaves_other_blocks_answers_alone() {
+    // two hexagons sharing vertex 5, with chords in each and one candidate
+    // whose path crosses both
+    let mut edges: Vec<_> = (0..6).map(|i| edge(i, (i + 1) % 6)).collect();
+    edges.extend((0..6).map(|i| edge(5 + i, 5 + (i + 1) % 6)));
+    let selected = edges.len();
+    let chords_a = [edge(0, 3), edge(0, 2), edge(1, 4), edge(2, 4)];
+    let chords_b = [edge(6, 8), edge(6, 9), edge(7, 10)];
+    edges.extend(chords_a);
+    edges.extend(chords_b);
+    edges.push(edge(1, 7));
+    let init: Vec<bool> = (0..edges.len()).map(|i| i < selected).collect();
+    let mut ps = PlanarSubgraph::new(11, &edges, &init).unwrap();
+
+    ps.query();
+    let before = ps.evaluations();
+    assert_eq!(before, chords_a.len() + chords_b.len() + 1);
+
+    ps.set(selected, true).unwrap();
+    for i in 0..chords_b.len() {
+        ps.is_addable(selected + chords_a.len() + i).unwrap();
+    }
+    assert_eq!(ps.evaluations(), before);
+
+    // the other chords of the grown block, and the crossing candidate
+    let mask = ps.query();
+    assert_eq!(ps.evaluations(), before + (chords_a.len() - 1) + 1);
+    let mut fresh_init = init.clone();
+    fresh_init[selected] = true;
+    let fresh = PlanarSubgraph::new(11, &edges, &fresh_init).unwrap();
+    assert_eq!(mask, fresh.query());
+}
+
+#[test]
+fn joining_and_splitting_blocks_leaves_other_blocks_answers_alone() {
+    // three hexagons in a chain, sharing vertices 5 and 10; the last has two
+    // chords, and one candidate joins the first two
+    let mut edges: Vec<_> = (0..15).map(|i| edge(i, i + 1)).collect();
+    edges.extend([edge(5, 0), edge(10, 5), edge(15, 10)]);
+    let selected = edges.len();
+    edges.extend([edge(10, 12), edge(11, 14), edge(1, 7)]);
+    let join = edges.len() - 1;
+    let init: Vec<bool> = (0..edges.len()).map(|i| i < selected).collect();
+    let mut ps = PlanarSubgraph::new(16, &edges, &init).unwrap();
+
+    ps.query();
+    let before = ps.evaluations();
+    assert_eq!(before, 3);
+
+    ps.set(join, true).unwrap();
+    assert!(ps.is_addable(selected).unwrap() && ps.is_addable(selected + 1).unwrap());
+    assert_eq!(ps.evaluations(), before);
+
+    ps.set(join, false).unwrap();
+    assert!(ps.is_addable(selected).unwrap() && ps.is_addable(selected + 1).unwrap());
+    assert_eq!(ps.evaluations(), before);
+    assert!(ps.is_addable(join).unwrap());
+    assert_eq!(ps.evaluations(), before + 1);
+}
+
+#[test]
+fn set_and_diff_evaluates_only_paths_through_touched_blocks() {
+    // the same chain of hexagons, with a chord in the first and two in the
+    // last
+    let mut edges: Vec<_> = (0..15).map(|i| edge(i, i + 1)).collect();
+    edges.extend([edge(5, 0), edge(10, 5), edge(15, 10)]);
+    let selected = edges.len();
+    edges.extend([edge(1, 3), edge(10, 12), edge(11, 14)]);
+    let (a, c1, c2) = (selected, selected + 1, selected + 2);
+    let init: Vec<bool> = (0..edges.len()).map(|i| i < selected).collect();
+    let mut ps = PlanarSubgraph::new(16, &edges, &init).unwrap();
+
+    // c1 and c2 before, c2 again after; a is never looked at
+    assert_eq!(ps.set_and_diff(c1, true).unwrap(), vec![c1, c2]);
+    assert_eq!(ps.evaluations(), 3);
+    assert!(ps.is_addable(a).unwrap());
+    assert_eq!(ps.evaluations(), 4);
+}
@@ -43,12 +43,12 @@ fn run_chain_test() {
     pairs.push(uv);
 
     let edges_all = mk_edges(&pairs);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     for i in 0..edges_all.len() {
         if i == uv_idx {
             continue;
         }
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
     }
 
     let spqr_mask = mask_from_spqr(&ps);
@@ -11,7 +11,7 @@ fn k5_subdivision_minus_one_edge_blocked() {
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
     let idx = pair_index(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let subdiv: std::collections::HashMap<graphum::Edge, usize> =
         [((0, 2), 5usize), ((0, 3), 6), ((1, 3), 7), ((2, 4), 8)]
@@ -21,10 +21,10 @@ fn k5_subdivision_minus_one_edge_blocked() {
             .collect();
     let add_path = |u: usize, v: usize, ps: &mut PlanarSubgraph| {
         if let Some(&w) = subdiv.get(&edge(u, v)) {
-            ps.set(idx[&edge(u, w)], true);
-            ps.set(idx[&edge(v, w)], true);
+            ps.set(idx[&edge(u, w)], true).unwrap();
+            ps.set(idx[&edge(v, w)], true).unwrap();
         } else {
-            ps.set(idx[&edge(u, v)], true);
+            ps.set(idx[&edge(u, v)], true).unwrap();
         }
     };
 
@@ -53,7 +53,7 @@ fn k33_subdivision_minus_one_edge_blocked() {
     let edges = edges_complete(n);
     let edges_all = mk_edges(&edges);
     let idx = pair_index(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let subdiv: std::collections::HashMap<graphum::Edge, usize> =
         [((0, 4), 6usize), ((1, 5), 7), ((2, 3), 8), ((2, 5), 9)]
@@ -63,10 +63,10 @@ fn k33_subdivision_minus_one_edge_blocked() {
             .collect();
     let add_path = |u: usize, v: usize, ps: &mut PlanarSubgraph| {
         if let Some(&w) = subdiv.get(&edge(u, v)) {
-            ps.set(idx[&edge(u, w)], true);
-            ps.set(idx[&edge(v, w)], true);
+            ps.set(idx[&edge(u, w)], true).unwrap();
+            ps.set(idx[&edge(v, w)], true).unwrap();
         } else {
-            ps.set(idx[&edge(u, v)], true);
+            ps.set(idx[&edge(u, v)], true).unwrap();
         }
     };
 
@@ -23,7 +23,7 @@ fn run_large_sparse_graph(m: usize) {
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mv0 = mask_from_spqr(&ps);
     assert_eq!(mv0.len(), edges.len());
@@ -37,9 +37,9 @@ fn run_large_sparse_graph(m: usize) {
     let readd_cap = if m <= 20_000 { 250 } else { 350 };
 
     let baseline_mask = |selected: &HashSet<usize>| {
-        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
         for i in selected.iter().copied().collect::<Vec<_>>() {
-            base.set(i, true);
+            base.set(i, true).unwrap();
         }
         mask_from_spqr(&base)
     };
@@ -57,7 +57,7 @@ fn run_large_sparse_graph(m: usize) {
                 break;
             }
             let i = *legal.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
             let mv_after = mask_from_spqr(&ps);
             assert!(!mv_after[i]);
@@ -73,7 +73,7 @@ fn run_large_sparse_graph(m: usize) {
             let mut sel_list: Vec<_> = selected.iter().copied().collect();
             sel_list.shuffle(&mut rng);
             for ridx in sel_list {
-                ps.set(ridx, false);
+                ps.set(ridx, false).unwrap();
                 selected.remove(&ridx);
                 dels += 1;
                 if dels >= del_cap {
@@ -98,7 +98,7 @@ fn run_large_sparse_graph(m: usize) {
                 break;
             }
             let i = *legal.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
             let mv_after = mask_from_spqr(&ps);
             assert!(!mv_after[i]);
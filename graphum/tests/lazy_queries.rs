@@ -0,0 +1,65 @@
+mod common;
+
+use common::{edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+
+use graphum::{Error, spqr::PlanarSubgraph};
+
+#[test]
+fn single_queries_match_truth_without_full_mask() {
+    let mut rng = common::rng(0x1A2E);
+    let n = 14;
+    let edges = edges_random(n, 50, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+
+    for _ in 0..40 {
+        let truth = truth_addability_mask(n, &selected, &edges);
+        let probe: Vec<usize> = (0..edges.len()).collect();
+        let probe: Vec<usize> = probe.sample(&mut rng, 6).copied().collect();
+        for &i in &probe {
+            assert_eq!(ps.is_addable(i).unwrap(), truth[i], "edge {i}");
+        }
+        let expect: Vec<bool> = probe.iter().map(|&i| truth[i]).collect();
+        assert_eq!(ps.addable_among(&probe).unwrap(), expect);
+
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| truth[i]).collect();
+        match legal.choose(&mut rng) {
+            Some(&i) => {
+                ps.set(i, true).unwrap();
+                selected.insert(i);
+            }
+            None => break,
+        }
+    }
+}
+
+#[test]
+fn mixing_lazy_and_full_queries_agrees() {
+    let n = 6;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+    for i in [0, 1, 2, 5, 6, 9] {
+        ps.set(i, true).unwrap();
+        selected.insert(i);
+        // Resolve a few entries before the full mask fills in the rest.
+        ps.addable_among(&[3, 4, 14]).unwrap();
+        assert_eq!(ps.query(), truth_addability_mask(n, &selected, &edges));
+    }
+    assert!(!ps.is_addable(0).unwrap());
+}
+
+#[test]
+fn lazy_queries_reject_bad_ids() {
+    let edges = mk_edges(&edges_complete(4));
+    let ps = PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).unwrap();
+    let err = Error::EdgeOutOfRange {
+        edge: 6,
+        num_edges: 6,
+    };
+    assert_eq!(ps.is_addable(6), Err(err));
+    assert_eq!(ps.addable_among(&[0, 6]), Err(err));
+    assert_eq!(ps.addable_among(&[]), Ok(vec![]));
+}
@@ -37,9 +37,9 @@ fn fill_from_triangulated_polygon_reaches_maximal_planar() {
     let sel = greedily_fill_to_maximal_planar(n, &edges, base_idx_pruned.clone());
     assert_eq!(sel.len(), 3 * n - 6);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     for i in sel.iter().copied() {
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
     }
     let mask = mask_from_spqr(&ps);
     assert!(
@@ -71,9 +71,9 @@ fn fill_from_two_components_reaches_global_maximal_planar() {
     let sel = greedily_fill_to_maximal_planar(n, &edges, base_idx);
     assert_eq!(sel.len(), 3 * n - 6);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     for i in sel.iter().copied() {
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
     }
     let mask = mask_from_spqr(&ps);
     assert!(
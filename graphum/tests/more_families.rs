@@ -35,12 +35,12 @@ fn ladder_family_truth_mask_matches_spqr() {
         let edges = edges_complete(n);
         let edges_all = mk_edges(&edges);
         let idx = pair_index(&edges);
-        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
         let e = ladder_edges(k);
         let selected: HashSet<usize> = e.iter().map(|edge| idx[edge]).collect();
         for i in &selected {
-            ps.set(*i, true);
+            ps.set(*i, true).unwrap();
         }
 
         let mv = mask_from_spqr(&ps);
@@ -61,12 +61,12 @@ fn prism_family_truth_mask_matches_spqr() {
         let edges = edges_complete(n);
         let edges_all = mk_edges(&edges);
         let idx = pair_index(&edges);
-        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
         let e = prism_edges(m);
         let selected: HashSet<usize> = e.iter().map(|edge| idx[edge]).collect();
         for i in &selected {
-            ps.set(*i, true);
+            ps.set(*i, true).unwrap();
         }
 
         let mv = mask_from_spqr(&ps);
@@ -20,13 +20,13 @@ fn run_dynamic_vs_reset(seed: u64) {
     let steps = 160;
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let mut selected: HashSet<usize> = HashSet::new();
 
     for _ in 0..steps {
-        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
         for i in selected.iter().copied().collect::<Vec<_>>() {
-            base.set(i, true);
+            base.set(i, true).unwrap();
         }
         let mask_dyn = mask_from_spqr(&ps);
         let mask_base = mask_from_spqr(&base);
@@ -39,7 +39,7 @@ fn run_dynamic_vs_reset(seed: u64) {
             .collect();
         if !legal_idx.is_empty() && (selected.is_empty() || rng.random_range(0.0..1.0) < 0.7) {
             let i = *legal_idx.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
         } else if !selected.is_empty() {
             let i = *selected
@@ -48,7 +48,7 @@ fn run_dynamic_vs_reset(seed: u64) {
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .unwrap();
-            ps.set(i, false);
+            ps.set(i, false).unwrap();
             selected.remove(&i);
         }
     }
@@ -68,7 +68,7 @@ fn run_dynamic_vs_networkx(seed: u64) {
     let steps = 100;
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let mut selected: HashSet<usize> = HashSet::new();
 
     for _ in 0..steps {
@@ -88,7 +88,7 @@ fn run_dynamic_vs_networkx(seed: u64) {
             .collect();
         if !legal_idx.is_empty() && (selected.is_empty() || rng.random_range(0.0..1.0) < 0.6) {
             let i = *legal_idx.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
         } else if !selected.is_empty() {
             let i = *selected
@@ -97,7 +97,7 @@ fn run_dynamic_vs_networkx(seed: u64) {
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .unwrap();
-            ps.set(i, false);
+            ps.set(i, false).unwrap();
             selected.remove(&i);
         }
     }
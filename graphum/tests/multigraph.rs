@@ -0,0 +1,139 @@
+mod common;
+
+use common::{boyer_myrvold, edge, edges_complete, edges_random, mk_edges};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::{Edge, spqr::PlanarSubgraph};
+
+/// Addability over a multigraph, checked against the simple graph underlying
+/// the selection: loops and parallels of a selected edge never matter.
+fn multigraph_truth(n: usize, selected: &HashSet<usize>, edges: &[Edge]) -> Vec<bool> {
+    let mut pairs: Vec<Edge> = selected
+        .iter()
+        .map(|&i| edges[i])
+        .filter(|e| e.u != e.v)
+        .map(|e| edge(e.u, e.v))
+        .collect();
+    pairs.sort_by_key(|e| (e.u, e.v));
+    pairs.dedup();
+
+    (0..edges.len())
+        .map(|i| {
+            let e = edges[i];
+            if selected.contains(&i) {
+                return false;
+            }
+            if e.u == e.v || pairs.contains(&edge(e.u, e.v)) {
+                return true;
+            }
+            let mut with = pairs.clone();
+            with.push(edge(e.u, e.v));
+            boyer_myrvold(n, &with).0
+        })
+        .collect()
+}
+
+#[test]
+fn parallel_of_selected_edge_stays_addable_when_saturated() {
+    let n = 5;
+    let mut edges = mk_edges(&edges_complete(n));
+    let missing = edges.pop().unwrap();
+    let m = edges.len();
+    edges.push(edges[0]);
+    edges.push(Edge {
+        u: edges[0].v,
+        v: edges[0].u,
+    });
+    edges.push(missing);
+
+    let mut added = vec![true; m];
+    added.extend([false; 3]);
+    let ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+
+    assert!(ps.is_addable(m).unwrap());
+    assert!(ps.is_addable(m + 1).unwrap());
+    assert!(!ps.is_addable(m + 2).unwrap());
+    assert!(ps.conflicts(m).unwrap().is_empty());
+    assert_eq!(ps.obstruction(m).unwrap(), None);
+    assert!(ps.obstruction(m + 2).unwrap().is_some());
+}
+
+#[test]
+fn deselecting_one_of_two_parallels_keeps_the_pair_adjacent() {
+    let n = 5;
+    let mut edges = mk_edges(&edges_complete(n));
+    let missing = edges.pop().unwrap();
+    let m = edges.len();
+    edges.push(edges[0]);
+    edges.push(missing);
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    for i in 0..=m {
+        ps.set(i, true).unwrap();
+    }
+    assert!(!ps.is_addable(m + 1).unwrap());
+
+    // Edge 0 is still present through its parallel copy.
+    ps.set(0, false).unwrap();
+    assert!(ps.is_addable(0).unwrap());
+    assert!(!ps.is_addable(m + 1).unwrap());
+
+    // Dropping the copy as well leaves room for the missing edge.
+    ps.set(m, false).unwrap();
+    assert!(ps.is_addable(m + 1).unwrap());
+}
+
+#[test]
+fn self_loops_are_always_addable() {
+    let n = 5;
+    let mut edges = mk_edges(&edges_complete(n));
+    let m = edges.len();
+    edges.push(Edge { u: 2, v: 2 });
+    let mut added = vec![true; m];
+    added.truncate(m - 1);
+    added.extend([false, false]);
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+    assert!(ps.is_addable(m).unwrap());
+    assert!(ps.can_add(2, 2).unwrap());
+    assert!(ps.conflicts(m).unwrap().is_empty());
+
+    ps.set(m, true).unwrap();
+    assert!(!ps.is_addable(m).unwrap());
+    assert!(!ps.is_addable(m - 1).unwrap());
+    assert!(ps.embedding().is_some());
+}
+
+#[test]
+fn random_multigraph_updates_match_truth() {
+    let mut rng = common::rng(0x3317);
+    let n = 10;
+    let mut edges = edges_random(n, 25, &mut rng);
+    for _ in 0..8 {
+        let &e = edges.choose(&mut rng).unwrap();
+        edges.push(e);
+    }
+    for _ in 0..4 {
+        let v = rng.random_range(0..n);
+        edges.push(Edge { u: v, v });
+    }
+
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+    for step in 0..60 {
+        let i = rng.random_range(0..edges.len());
+        if selected.contains(&i) {
+            ps.set(i, false).unwrap();
+            selected.remove(&i);
+        } else if ps.is_addable(i).unwrap() {
+            ps.set(i, true).unwrap();
+            selected.insert(i);
+        }
+        assert_eq!(
+            ps.query(),
+            multigraph_truth(n, &selected, &edges),
+            "step {step}"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{edge, edges_complete, edges_random, mk_edges, truth_addability_mask};
+use rand::seq::IndexedRandom;
+use std::collections::HashSet;
+
+use graphum::{
+    Edge,
+    mps::{KuratowskiKind, kuratowski_subdivision},
+    spqr::PlanarSubgraph,
+};
+
+fn kind_of(n: usize, edges: &[Edge], ids: &[usize]) -> KuratowskiKind {
+    let sub: Vec<Edge> = ids.iter().map(|&i| edges[i]).collect();
+    kuratowski_subdivision(n, &sub).unwrap().unwrap().kind
+}
+
+#[test]
+fn k5_and_k33_obstructions() {
+    let k5 = mk_edges(&edges_complete(5));
+    let mut sel = vec![true; k5.len()];
+    sel[9] = false;
+    let ps = PlanarSubgraph::new(5, &k5, &sel).unwrap();
+    let ids = ps.obstruction(9).unwrap().unwrap();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    assert_eq!(kind_of(5, &k5, &ids), KuratowskiKind::K5);
+
+    let k33: Vec<Edge> = (0..3)
+        .flat_map(|a| (3..6).map(move |b| edge(a, b)))
+        .collect();
+    let mut sel = vec![true; k33.len()];
+    sel[4] = false;
+    let ps = PlanarSubgraph::new(6, &k33, &sel).unwrap();
+    let ids = ps.obstruction(4).unwrap().unwrap();
+    assert_eq!(ids.len(), 9);
+    assert_eq!(kind_of(6, &k33, &ids), KuratowskiKind::K33);
+}
+
+#[test]
+fn no_obstruction_for_addable_or_selected() {
+    let k5 = mk_edges(&edges_complete(5));
+    let mut sel = vec![true; k5.len()];
+    sel[8] = false;
+    sel[9] = false;
+    let ps = PlanarSubgraph::new(5, &k5, &sel).unwrap();
+    assert!(ps.is_addable(9).unwrap());
+    assert_eq!(ps.obstruction(9).unwrap(), None);
+    assert_eq!(ps.obstruction(0).unwrap(), None);
+}
+
+#[test]
+fn random_obstructions_are_kuratowski_subgraphs() {
+    let mut rng = common::rng(0x0B57);
+    let n = 15;
+    let edges = edges_random(n, 70, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = HashSet::new();
+    loop {
+        let mask = ps.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let Some(&i) = legal.choose(&mut rng) else {
+            break;
+        };
+        ps.set(i, true).unwrap();
+        selected.insert(i);
+    }
+
+    let truth = truth_addability_mask(n, &selected, &edges);
+    for i in (0..edges.len()).filter(|i| !selected.contains(i)) {
+        assert!(!truth[i]);
+        let ids = ps.obstruction(i).unwrap().unwrap();
+        assert!(ids.contains(&i), "edge {i}");
+        assert!(ids.iter().all(|j| *j == i || selected.contains(j)));
+        kind_of(n, &edges, &ids);
+    }
+}
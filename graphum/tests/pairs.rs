@@ -0,0 +1,63 @@
+mod common;
+
+use common::{boyer_myrvold, edge, edges_random, grid_edges};
+use rand::seq::IndexedRandom;
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+fn planar_with(n: usize, selected: &[Edge], extra: Edge) -> bool {
+    let mut all = selected.to_vec();
+    all.push(extra);
+    boyer_myrvold(n, &all).0
+}
+
+#[test]
+fn undeclared_pairs_match_truth() {
+    let mut rng = common::rng(0x9A15);
+    let n = 13;
+    // Declare only a few candidates; most pairs asked about are new.
+    let edges = edges_random(n, 30, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = Vec::new();
+    loop {
+        let mask = ps.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let Some(&i) = legal.choose(&mut rng) else {
+            break;
+        };
+        ps.set(i, true).unwrap();
+        selected.push(edges[i]);
+    }
+
+    for u in 0..n {
+        let mut partners = Vec::new();
+        for w in 0..n {
+            let expect = u == w || planar_with(n, &selected, edge(u, w));
+            assert_eq!(ps.can_add(u, w).unwrap(), expect, "({u}, {w})");
+            if u != w && expect {
+                partners.push(w);
+            }
+        }
+        assert_eq!(ps.addable_partners(u).unwrap(), partners, "vertex {u}");
+    }
+}
+
+#[test]
+fn grid_partners_stay_on_shared_faces() {
+    // Every other vertex of a 3x3 grid shares a square with the centre.
+    let edges = grid_edges(3, 3);
+    let ps = PlanarSubgraph::new(9, &edges, &vec![true; edges.len()]).unwrap();
+    assert_eq!(
+        ps.addable_partners(4).unwrap(),
+        vec![0, 1, 2, 3, 5, 6, 7, 8]
+    );
+    // Opposite corners of the grid are on the outer face together.
+    assert!(ps.can_add(0, 8).unwrap());
+    assert_eq!(
+        ps.can_add(0, 9),
+        Err(Error::NoSuchVertex {
+            vertex: 9,
+            num_verts: 9
+        })
+    );
+}
@@ -28,13 +28,13 @@ fn run_random_sequence_matches_reset_baseline(seed: u64) {
     let steps = 120;
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let mut selected: HashSet<usize> = HashSet::new();
     for _ in 0..steps {
-        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
         for i in selected.iter().copied().collect::<Vec<_>>() {
-            base.set(i, true);
+            base.set(i, true).unwrap();
         }
         let mask_dyn = mask_from_spqr(&ps);
         let mask_base = mask_from_spqr(&base);
@@ -48,7 +48,7 @@ fn run_random_sequence_matches_reset_baseline(seed: u64) {
             .collect();
         if !legal_idx.is_empty() && (selected.is_empty() || rng.random_range(0.0..1.0) < 0.7) {
             let act = *legal_idx.choose(&mut rng).unwrap();
-            ps.set(act, true);
+            ps.set(act, true).unwrap();
             selected.insert(act);
         } else if !selected.is_empty() {
             let ridx = *selected
@@ -57,7 +57,7 @@ fn run_random_sequence_matches_reset_baseline(seed: u64) {
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .unwrap();
-            ps.set(ridx, false);
+            ps.set(ridx, false).unwrap();
             selected.remove(&ridx);
         }
     }
@@ -80,7 +80,7 @@ fn run_random_sequence_matches_networkx(seed: u64) {
     let steps = 64;
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let mut selected: HashSet<usize> = HashSet::new();
 
     for _ in 0..steps {
@@ -100,7 +100,7 @@ fn run_random_sequence_matches_networkx(seed: u64) {
             .collect();
         if !legal_idx.is_empty() && (selected.is_empty() || rng.random_range(0.0..1.0) < 0.6) {
             let i = *legal_idx.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
         } else if !selected.is_empty() {
             let i = *selected
@@ -109,7 +109,7 @@ fn run_random_sequence_matches_networkx(seed: u64) {
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .unwrap();
-            ps.set(i, false);
+            ps.set(i, false).unwrap();
             selected.remove(&i);
         }
     }
@@ -21,14 +21,14 @@ fn reordering_candidate_list_does_not_change_masks() {
 
     let ea1 = mk_edges(&pairs);
     let ea2 = mk_edges(&pairs_perm);
-    let mut ps1 = PlanarSubgraph::new(n, &ea1, &vec![false; ea1.len()]);
-    let mut ps2 = PlanarSubgraph::new(n, &ea2, &vec![false; ea2.len()]);
+    let mut ps1 = PlanarSubgraph::new(n, &ea1, &vec![false; ea1.len()]).unwrap();
+    let mut ps2 = PlanarSubgraph::new(n, &ea2, &vec![false; ea2.len()]).unwrap();
 
     let base_pairs = build_triangulated_polygon_edges(n);
     let sel_idx: Vec<usize> = base_pairs.iter().map(|edge| idx[edge]).collect();
     for &i in &sel_idx {
-        ps1.set(i, true);
-        ps2.set(idx_perm[&pairs[i]], true);
+        ps1.set(i, true).unwrap();
+        ps2.set(idx_perm[&pairs[i]], true).unwrap();
     }
 
     for _ in 0..20 {
@@ -51,7 +51,7 @@ fn reordering_candidate_list_does_not_change_masks() {
             break;
         }
         let i = *legal.choose(&mut rng).unwrap();
-        ps1.set(i, true);
-        ps2.set(idx_perm[&pairs[i]], true);
+        ps1.set(i, true).unwrap();
+        ps2.set(idx_perm[&pairs[i]], true).unwrap();
     }
 }
@@ -19,7 +19,7 @@ fn run_idempotent_add_remove(seed: u64) {
     let m = 60;
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
 
     let m0 = mask_from_spqr(&ps);
 
@@ -31,8 +31,8 @@ fn run_idempotent_add_remove(seed: u64) {
     let take: Vec<usize> = legal.into_iter().take(8).collect();
 
     for &i in &take {
-        ps.set(i, true);
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
+        ps.set(i, true).unwrap();
     }
     let m1 = mask_from_spqr(&ps);
     for &i in &take {
@@ -44,8 +44,8 @@ fn run_idempotent_add_remove(seed: u64) {
     }
 
     for &i in take.iter().take(3) {
-        ps.set(i, false);
-        ps.set(i, false);
+        ps.set(i, false).unwrap();
+        ps.set(i, false).unwrap();
     }
     let m2 = mask_from_spqr(&ps);
     assert_eq!(m2.len(), m1.len());
@@ -10,7 +10,7 @@ fn n_0_and_1_have_no_candidates() {
     for n in [0, 1] {
         let edges = edges_complete(n);
         let ea = mk_edges(&edges);
-        let ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]);
+        let ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]).unwrap();
         let mv = mask_from_spqr(&ps);
         assert!(mv.is_empty());
     }
@@ -21,15 +21,15 @@ fn n_2_single_edge_roundtrip() {
     let n = 2;
     let edges = edges_complete(n);
     let ea = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]);
+    let mut ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]).unwrap();
     let mv0 = mask_from_spqr(&ps);
     assert_eq!(mv0, vec![true]);
 
-    ps.set(0, true);
+    ps.set(0, true).unwrap();
     let mv1 = mask_from_spqr(&ps);
     assert_eq!(mv1, vec![false]);
 
-    ps.set(0, false);
+    ps.set(0, false).unwrap();
     let mv2 = mask_from_spqr(&ps);
     let truth = truth_addability_mask(n, &HashSet::new(), &edges);
     assert_eq!(mv2, truth);
@@ -41,9 +41,9 @@ fn n_3_triangle_is_maximal_planar() {
     let n = 3;
     let edges = edges_complete(n);
     let ea = mk_edges(&edges);
-    let mut ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]);
+    let mut ps = PlanarSubgraph::new(n, &ea, &vec![false; ea.len()]).unwrap();
     for i in 0..edges.len() {
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
     }
 
     let mv = mask_from_spqr(&ps);
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{edges_random, mk_edges};
+use rand::seq::IndexedRandom;
+
+use graphum::spqr::PlanarSubgraph;
+
+#[test]
+fn snapshot_matches_query() {
+    let mut rng = common::rng(0xB175);
+    let n = 40;
+    // More than one word, and not a multiple of 64.
+    let edges = edges_random(n, 150, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+
+    for _ in 0..30 {
+        let mask = ps.query();
+        let snap = ps.snapshot();
+        assert_eq!(snap.len(), mask.len());
+        assert_eq!(snap.words().len(), mask.len().div_ceil(64));
+        for (i, &b) in mask.iter().enumerate() {
+            assert_eq!(snap.get(i), Some(b));
+        }
+        assert_eq!(snap.get(mask.len()), None);
+        let ones: Vec<usize> = (0..mask.len()).filter(|&i| mask[i]).collect();
+        assert_eq!(snap.iter_ones().collect::<Vec<_>>(), ones);
+        assert_eq!(snap.count_ones(), ones.len());
+
+        let Some(&i) = ones.choose(&mut rng) else {
+            break;
+        };
+        ps.set(i, true).unwrap();
+    }
+}
+
+#[test]
+fn snapshot_is_immutable_across_updates() {
+    let edges = mk_edges(&common::edges_complete(5));
+    let mut ps = PlanarSubgraph::new(5, &edges, &vec![false; edges.len()]).unwrap();
+    let before = ps.snapshot();
+    assert_eq!(before.count_ones(), 10);
+    for i in 0..9 {
+        ps.set(i, true).unwrap();
+    }
+    assert_eq!(before.count_ones(), 10);
+    let after = ps.reader().snapshot();
+    assert_eq!(after.iter_ones().count(), 0);
+    assert!(!after.is_empty());
+}
@@ -9,15 +9,15 @@ fn remove_edge_that_splits_component_makes_cross_pairs_addable() {
     let n = 6;
     let pairs = edges_complete(n);
     let edges_all = mk_edges(&pairs);
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let idx = common::pair_index(&pairs);
 
     let path: [(usize, usize); 5] = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)];
     for &(u, v) in &path {
-        ps.set(idx[&edge(u, v)], true);
+        ps.set(idx[&edge(u, v)], true).unwrap();
     }
 
-    ps.set(idx[&edge(2, 3)], false);
+    ps.set(idx[&edge(2, 3)], false).unwrap();
 
     let mv = ps.query();
 
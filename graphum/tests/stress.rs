@@ -16,13 +16,13 @@ fn rebuild_baseline_matches_dynamic_on_large_sequence() {
     let edges = edges_random(n, m, &mut rng);
     let edges_all = mk_edges(&edges);
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let mut selected: HashSet<usize> = HashSet::new();
 
     for _ in 0..steps {
-        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut base = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
         for i in selected.iter().copied().collect::<Vec<_>>() {
-            base.set(i, true);
+            base.set(i, true).unwrap();
         }
         let mask_dyn = mask_from_spqr(&ps);
         let mask_base = mask_from_spqr(&base);
@@ -35,7 +35,7 @@ fn rebuild_baseline_matches_dynamic_on_large_sequence() {
             .collect();
         if !legal_idx.is_empty() && (selected.is_empty() || rng.random_range(0.0..1.0) < 0.65) {
             let i = *legal_idx.choose(&mut rng).unwrap();
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
             selected.insert(i);
         } else if !selected.is_empty() {
             let i = *selected
@@ -44,7 +44,7 @@ fn rebuild_baseline_matches_dynamic_on_large_sequence() {
                 .collect::<Vec<_>>()
                 .choose(&mut rng)
                 .unwrap();
-            ps.set(i, false);
+            ps.set(i, false).unwrap();
             selected.remove(&i);
         }
     }
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{edges_complete, edges_random, mask_from_spqr, mk_edges, truth_addability_mask};
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+
+use graphum::{Error, spqr::PlanarSubgraph};
+
+#[test]
+fn commit_matches_individual_sets() {
+    let mut rng = common::rng(0x7A5);
+    let n = 16;
+    let edges = edges_random(n, 60, &mut rng);
+    let mut batched = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut stepwise = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+
+    let mut selected = HashSet::new();
+    for round in 0..6 {
+        let mut order: Vec<usize> = (0..edges.len()).collect();
+        order.shuffle(&mut rng);
+        batched.begin().unwrap();
+        let before = batched.query();
+        for &i in order.iter().take(8) {
+            if stepwise.query()[i] {
+                stepwise.set(i, true).unwrap();
+                batched.set(i, true).unwrap();
+                selected.insert(i);
+            } else if selected.remove(&i) {
+                stepwise.set(i, false).unwrap();
+                batched.set(i, false).unwrap();
+            }
+        }
+        assert_eq!(batched.query(), before, "mask moved mid-transaction");
+        batched.commit().unwrap();
+        assert_eq!(batched.query(), stepwise.query(), "round {round}");
+    }
+    assert_eq!(
+        mask_from_spqr(&batched),
+        truth_addability_mask(n, &selected, &edges)
+    );
+}
+
+#[test]
+fn rollback_restores_selection_and_mask() {
+    let n = 7;
+    let edges = mk_edges(&edges_complete(n));
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    for i in [0, 1, 6] {
+        ps.set(i, true).unwrap();
+    }
+    let before = ps.query();
+
+    ps.begin().unwrap();
+    for i in 0..edges.len() {
+        ps.set(i, i % 2 == 0).unwrap();
+    }
+    ps.rollback().unwrap();
+    assert_eq!(ps.query(), before);
+
+    // The restored selection must be what later updates build on.
+    ps.set(2, true).unwrap();
+    let selected: HashSet<usize> = [0, 1, 2, 6].into();
+    assert_eq!(ps.query(), truth_addability_mask(n, &selected, &edges));
+}
+
+#[test]
+fn transaction_misuse_is_an_error() {
+    let edges = mk_edges(&edges_complete(4));
+    let mut ps = PlanarSubgraph::new(4, &edges, &vec![false; edges.len()]).unwrap();
+    assert_eq!(ps.commit(), Err(Error::NoTransaction));
+    assert_eq!(ps.rollback(), Err(Error::NoTransaction));
+    ps.begin().unwrap();
+    assert_eq!(ps.begin(), Err(Error::TransactionActive));
+    ps.commit().unwrap();
+    assert_eq!(ps.commit(), Err(Error::NoTransaction));
+}
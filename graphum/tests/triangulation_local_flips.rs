@@ -16,9 +16,9 @@ fn remove_one_edge_from_triangulation_two_diagonals_addable() {
 
         let sel = greedily_fill_to_maximal_planar(n, &pairs, HashSet::new());
 
-        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+        let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
         for i in sel.iter().copied() {
-            ps.set(i, true);
+            ps.set(i, true).unwrap();
         }
 
         let candidates: Vec<usize> = sel
@@ -39,7 +39,7 @@ fn remove_one_edge_from_triangulation_two_diagonals_addable() {
         }
         let take = candidates.iter().take(candidates.len().min(5)).copied();
         for ridx in take {
-            ps.set(ridx, false);
+            ps.set(ridx, false).unwrap();
             let mut selected = sel.clone();
             selected.remove(&ridx);
 
@@ -64,7 +64,7 @@ fn remove_one_edge_from_triangulation_two_diagonals_addable() {
             }
             assert_eq!(mv, truth);
 
-            ps.set(ridx, true);
+            ps.set(ridx, true).unwrap();
         }
     }
 }
@@ -145,14 +145,14 @@ fn remove_two_disjoint_edges_yields_two_independent_quadrilaterals() {
         return;
     };
 
-    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]);
+    let mut ps = PlanarSubgraph::new(n, &edges_all, &vec![false; edges_all.len()]).unwrap();
     let mut sel_sorted: Vec<usize> = sel.iter().copied().collect();
     sel_sorted.sort_unstable();
     for i in sel_sorted {
-        ps.set(i, true);
+        ps.set(i, true).unwrap();
     }
-    ps.set(ridx1, false);
-    ps.set(ridx2, false);
+    ps.set(ridx1, false).unwrap();
+    ps.set(ridx2, false).unwrap();
 
     let mut selected = sel.clone();
     selected.remove(&ridx1);
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{edge, edges_complete, mk_edges, truth_addability_mask};
+use rand::{RngExt, seq::IndexedRandom};
+use std::collections::HashSet;
+
+use graphum::{Edge, Error, spqr::PlanarSubgraph};
+
+#[test]
+fn removing_a_vertex_retires_its_candidates() {
+    let n = 5;
+    let edges = mk_edges(&edges_complete(n));
+    let missing = edges.iter().position(|&e| e == edge(0, 1)).unwrap();
+    let added: Vec<bool> = (0..edges.len()).map(|i| i != missing).collect();
+    let mut ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+    assert!(!ps.is_addable(missing).unwrap());
+
+    let incident: Vec<usize> = (0..edges.len())
+        .filter(|&i| edges[i].u == 4 || edges[i].v == 4)
+        .collect();
+    assert_eq!(ps.remove_vertex(4).unwrap(), incident);
+    assert!(!ps.is_vertex(4));
+    assert_eq!(ps.num_verts(), n);
+    assert!(ps.is_addable(missing).unwrap());
+    for &i in &incident {
+        assert!(!ps.is_candidate(i));
+        assert!(!ps.query()[i]);
+    }
+
+    assert_eq!(ps.remove_vertex(4), Err(Error::RemovedVertex { vertex: 4 }));
+    assert_eq!(ps.can_add(0, 4), Err(Error::RemovedVertex { vertex: 4 }));
+    assert_eq!(
+        ps.add_candidate(edge(2, 4)),
+        Err(Error::RemovedVertex { vertex: 4 })
+    );
+    assert_eq!(
+        ps.remove_vertex(5),
+        Err(Error::NoSuchVertex {
+            vertex: 5,
+            num_verts: 5
+        })
+    );
+    assert!(!ps.addable_partners(0).unwrap().contains(&4));
+}
+
+#[test]
+fn new_vertex_starts_isolated() {
+    let n = 5;
+    let edges = mk_edges(&edges_complete(n));
+    let mut added = vec![true; edges.len()];
+    added[edges.len() - 1] = false;
+    let mut ps = PlanarSubgraph::new(n, &edges, &added).unwrap();
+    let before = ps.query();
+
+    let v = ps.add_vertex().unwrap();
+    assert_eq!(v, n);
+    assert_eq!(ps.num_verts(), n + 1);
+    assert_eq!(ps.query(), before);
+    assert_eq!(ps.addable_partners(v).unwrap(), (0..n).collect::<Vec<_>>());
+
+    // Edges to the new vertex fit in any face of the K5 minus an edge.
+    let a = ps.add_candidate(edge(0, v)).unwrap();
+    let b = ps.add_candidate(edge(1, v)).unwrap();
+    assert!(ps.is_addable(a).unwrap());
+    ps.set(a, true).unwrap();
+    assert!(ps.is_addable(b).unwrap());
+
+    ps.begin().unwrap();
+    assert_eq!(ps.add_vertex(), Err(Error::TransactionActive));
+    assert_eq!(ps.remove_vertex(0), Err(Error::TransactionActive));
+    ps.rollback().unwrap();
+
+    let fork = ps.fork().unwrap();
+    assert_eq!(fork.num_verts(), n + 1);
+    assert_eq!(fork.query(), ps.query());
+}
+
+#[test]
+fn growing_and_retiring_sites_matches_truth() {
+    let mut rng = common::rng(0x517E);
+    let mut ps = PlanarSubgraph::new(0, &[], &[]).unwrap();
+
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut verts: Vec<usize> = Vec::new();
+    let mut live: HashSet<usize> = HashSet::new();
+    let mut selected: HashSet<usize> = HashSet::new();
+
+    for step in 0..200 {
+        match rng.random_range(0..8) {
+            0 | 1 => verts.push(ps.add_vertex().unwrap()),
+            2 => {
+                let Some(&v) = verts.choose(&mut rng) else {
+                    continue;
+                };
+                let retired = ps.remove_vertex(v).unwrap();
+                let mut expect: Vec<usize> = live
+                    .iter()
+                    .copied()
+                    .filter(|&i| edges[i].u == v || edges[i].v == v)
+                    .collect();
+                expect.sort();
+                assert_eq!(retired, expect, "step {step}");
+                for i in retired {
+                    live.remove(&i);
+                    selected.remove(&i);
+                }
+                verts.retain(|&w| w != v);
+            }
+            3..=5 => {
+                if verts.len() < 2 {
+                    continue;
+                }
+                let pair: Vec<usize> = verts.sample(&mut rng, 2).copied().collect();
+                let e = edge(pair[0], pair[1]);
+                if edges.contains(&e) {
+                    continue;
+                }
+                let id = ps.add_candidate(e).unwrap();
+                assert_eq!(id, edges.len());
+                edges.push(e);
+                live.insert(id);
+            }
+            _ => {
+                let mask = ps.query();
+                let legal: Vec<usize> = live.iter().copied().filter(|&i| mask[i]).collect();
+                let Some(&id) = legal.choose(&mut rng) else {
+                    continue;
+                };
+                ps.set(id, true).unwrap();
+                selected.insert(id);
+            }
+        }
+
+        let n = ps.num_verts();
+        let truth = truth_addability_mask(n, &selected, &edges);
+        let mask = ps.query();
+        for id in 0..edges.len() {
+            assert_eq!(
+                mask[id],
+                live.contains(&id) && truth[id],
+                "step {step}, edge {id}"
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::{edge, edges_random, grid_edges};
+use rand::seq::IndexedRandom;
+
+use graphum::{Edge, spqr::PlanarSubgraph};
+
+fn find(parent: &mut [usize], x: usize) -> usize {
+    if parent[x] != x {
+        parent[x] = find(parent, parent[x]);
+    }
+    parent[x]
+}
+
+/// Component label of each vertex over `edges`.
+fn components(n: usize, edges: &[Edge]) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..n).collect();
+    for e in edges {
+        let (a, b) = (find(&mut parent, e.u), find(&mut parent, e.v));
+        parent[a] = b;
+    }
+    (0..n).map(|x| find(&mut parent, x)).collect()
+}
+
+#[test]
+fn witness_faces_hold_both_endpoints() {
+    let mut rng = common::rng(0xFACE);
+    let n = 16;
+    let edges = edges_random(n, 60, &mut rng);
+    let mut ps = PlanarSubgraph::new(n, &edges, &vec![false; edges.len()]).unwrap();
+    let mut selected = Vec::new();
+    for _ in 0..22 {
+        let mask = ps.query();
+        let legal: Vec<usize> = (0..edges.len()).filter(|&i| mask[i]).collect();
+        let Some(&i) = legal.choose(&mut rng) else {
+            break;
+        };
+        ps.set(i, true).unwrap();
+        selected.push(i);
+    }
+    let sel_edges: Vec<Edge> = selected.iter().map(|&i| edges[i]).collect();
+    let comp = components(n, &sel_edges);
+    let num_comps = {
+        let mut c = comp.clone();
+        c.sort();
+        c.dedup();
+        c.len()
+    };
+
+    let mask = ps.query();
+    for i in 0..edges.len() {
+        let wf = ps.witness_face(i).unwrap();
+        if !mask[i] {
+            assert!(wf.is_none(), "edge {i}");
+            continue;
+        }
+        let wf = wf.unwrap();
+        let emb = &wf.embedding;
+
+        let mut darts: Vec<usize> = (0..n).flat_map(|v| emb.rotation(v).to_vec()).collect();
+        darts.sort();
+        let mut expect: Vec<usize> = selected.iter().flat_map(|&j| [j, j]).collect();
+        expect.sort();
+        assert_eq!(darts, expect, "edge {i}: rotation must cover the selection");
+
+        let faces = emb.faces();
+        let isolated = (0..n).filter(|&v| emb.rotation(v).is_empty()).count();
+        assert_eq!(
+            n + faces.len(),
+            sel_edges.len() + 2 * (num_comps - isolated) + isolated,
+            "edge {i}: not a planar embedding"
+        );
+
+        let Edge { u, v } = edges[i];
+        let touches = |x: usize| {
+            wf.boundary
+                .iter()
+                .flatten()
+                .any(|&e| edges[e].u == x || edges[e].v == x)
+        };
+        let has_edges = |x: usize| !emb.rotation(x).is_empty();
+        assert!(!has_edges(u) || touches(u), "edge {i}: {u} off the face");
+        assert!(!has_edges(v) || touches(v), "edge {i}: {v} off the face");
+        if comp[u] == comp[v] {
+            assert_eq!(wf.boundary.len(), 1);
+            assert!(faces.contains(&wf.boundary[0]));
+        }
+    }
+}
+
+#[test]
+fn grid_diagonal_lands_in_its_square() {
+    // In the 3x3 grid, corner 0 and centre 4 share only the top-left square.
+    let mut edges = grid_edges(3, 3);
+    let diag = edges.len();
+    edges.push(edge(0, 4));
+    let mut mask = vec![true; edges.len()];
+    mask[diag] = false;
+    let ps = PlanarSubgraph::new(9, &edges, &mask).unwrap();
+
+    let wf = ps.witness_face(diag).unwrap().unwrap();
+    assert_eq!(wf.boundary.len(), 1);
+    let mut face = wf.boundary[0].clone();
+    face.sort();
+    let mut square: Vec<usize> = [edge(0, 1), edge(1, 4), edge(3, 4), edge(0, 3)]
+        .iter()
+        .map(|e| edges.iter().position(|x| x == e).unwrap())
+        .collect();
+    square.sort();
+    assert_eq!(face, square);
+    assert!(ps.witness_face(0).unwrap().is_none());
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use common::{edge, edges_complete, grid_edges};
+use std::collections::HashSet;
+
+use graphum::mps::{
+    BoyerMyrvoldOptions, KuratowskiKind, KuratowskiWitness, boyer_myrvold_witness,
+    boyer_myrvold_witness_ids, kuratowski_subdivision, kuratowski_witnesses,
+};
+
+fn two_k5s() -> Vec<graphum::Edge> {
+    let mut edges = edges_complete(5);
+    edges.extend(edges_complete(5).iter().map(|e| edge(e.u + 5, e.v + 5)));
+    edges
+}
+
+#[test]
+fn planar_graph_has_no_witnesses() {
+    let opts = BoyerMyrvoldOptions {
+        max_witnesses: None,
+        ..Default::default()
+    };
+    assert!(
+        kuratowski_witnesses(12, &grid_edges(4, 3), &opts)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn default_options_extract_one_witness() {
+    let edges = edges_complete(6);
+    let ws = kuratowski_witnesses(6, &edges, &BoyerMyrvoldOptions::default()).unwrap();
+    assert_eq!(ws.len(), 1);
+}
+
+#[test]
+fn witnesses_are_distinct_bounded_subgraphs() {
+    let edges = two_k5s();
+    let pairs: HashSet<_> = edges.iter().copied().collect();
+    for bundles in [false, true] {
+        let opts = BoyerMyrvoldOptions {
+            max_witnesses: Some(4),
+            bundles,
+            ..Default::default()
+        };
+        let ws = kuratowski_witnesses(10, &edges, &opts).unwrap();
+        assert!(!ws.is_empty() && ws.len() <= 4);
+        for (i, w) in ws.iter().enumerate() {
+            assert!(w.edges.iter().all(|e| pairs.contains(e)));
+            assert!(ws[..i].iter().all(|o| o.edges != w.edges));
+        }
+    }
+}
+
+#[test]
+fn seeded_random_dfs_is_reproducible() {
+    let edges = edges_complete(7);
+    let opts = BoyerMyrvoldOptions {
+        max_witnesses: None,
+        random_dfs_seed: Some(17),
+        avoid_e2_minors: false,
+        ..Default::default()
+    };
+    let a = kuratowski_witnesses(7, &edges, &opts).unwrap();
+    let b = kuratowski_witnesses(7, &edges, &opts).unwrap();
+    assert!(!a.is_empty());
+    assert_eq!(a, b);
+}
+
+#[test]
+fn zero_witness_limit_is_rejected() {
+    let opts = BoyerMyrvoldOptions {
+        max_witnesses: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(
+        kuratowski_witnesses(6, &edges_complete(6), &opts),
+        Err(graphum::Error::ZeroWitnessLimit)
+    );
+}
+
+#[test]
+fn seeded_runs_agree_across_threads() {
+    let edges = edges_complete(7);
+    let opts = BoyerMyrvoldOptions {
+        max_witnesses: None,
+        random_dfs_seed: Some(17),
+        avoid_e2_minors: false,
+        ..Default::default()
+    };
+    let expected = kuratowski_witnesses(7, &edges, &opts).unwrap();
+    std::thread::scope(|s| {
+        let runs: Vec<_> = (0..4)
+            .map(|k| {
+                let (edges, expected) = (&edges, &expected);
+                s.spawn(move || {
+                    let other = BoyerMyrvoldOptions {
+                        random_dfs_seed: Some(k),
+                        ..opts
+                    };
+                    for _ in 0..8 {
+                        kuratowski_witnesses(7, edges, &other).unwrap();
+                        assert_eq!(&kuratowski_witnesses(7, edges, &opts).unwrap(), expected);
+                    }
+                })
+            })
+            .collect();
+        for r in runs {
+            r.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn k5_witness_is_classified() {
+    let sub = kuratowski_subdivision(5, &edges_complete(5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(sub.kind, KuratowskiKind::K5);
+    assert_eq!(sub.branch_vertices, vec![0, 1, 2, 3, 4]);
+    assert_eq!(sub.paths.len(), 10);
+    assert!(sub.paths.iter().all(|p| p.len() == 2));
+}
+
+#[test]
+fn subdivided_k33_paths_follow_subdivision_vertices() {
+    // K3,3 on {0,1,2} x {3,4,5}, with (0,4) routed through 6 and (2,5) through 7, 8.
+    let mut edges = Vec::new();
+    for u in [0, 1, 2] {
+        for v in [3, 4, 5] {
+            match (u, v) {
+                (0, 4) => edges.extend([edge(0, 6), edge(6, 4)]),
+                (2, 5) => edges.extend([edge(2, 7), edge(7, 8), edge(8, 5)]),
+                _ => edges.push(edge(u, v)),
+            }
+        }
+    }
+    let sub = kuratowski_subdivision(9, &edges).unwrap().unwrap();
+    assert_eq!(sub.kind, KuratowskiKind::K33);
+    assert_eq!(sub.branch_vertices, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(sub.paths.len(), 9);
+    assert!(sub.paths.contains(&vec![0, 6, 4]));
+    assert!(sub.paths.contains(&vec![2, 7, 8, 5]));
+    let path_edges: usize = sub.paths.iter().map(|p| p.len() - 1).sum();
+    assert_eq!(path_edges, edges.len());
+}
+
+#[test]
+fn non_subdivisions_are_not_classified() {
+    let k4 = KuratowskiWitness {
+        edges: edges_complete(4),
+        edge_ids: (0..6).collect(),
+    };
+    assert!(k4.subdivision().is_none());
+    assert!(
+        kuratowski_subdivision(12, &grid_edges(4, 3))
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn witness_ids_index_the_input_slice() {
+    // K5 on vertices 10..15, with a planar tail and a parallel copy of (10, 11).
+    let mut edges = vec![edge(0, 1), edge(1, 2)];
+    edges.extend(edges_complete(5).iter().map(|e| edge(e.u + 10, e.v + 10)));
+    edges.push(edge(10, 11));
+    let ids = boyer_myrvold_witness_ids(15, &edges).unwrap().unwrap();
+    assert_eq!(ids, (2..12).collect::<Vec<_>>());
+
+    let pairs = boyer_myrvold_witness(15, &edges).unwrap().unwrap();
+    let mut from_ids: Vec<_> = ids.iter().map(|&i| edges[i]).collect();
+    from_ids.sort_by_key(|e| (e.u, e.v));
+    assert_eq!(pairs, from_ids);
+}
@@ -1,5 +1,6 @@
 #pragma once
 
+#include <cstdint>
 #include <vector>
 
 #include "types.hpp"
@@ -11,4 +12,31 @@ namespace graph
     std::vector<Edge> boyer_myrvold_witness(
         std::size_t n_vertices, const std::vector<Edge> &edges);
 
+    // returns an empty vector if the graph is planar; otherwise the sorted
+    // indices into `edges` of a Kuratowski subgraph
+    std::vector<std::size_t> boyer_myrvold_witness_ids(
+        std::size_t n_vertices, const std::vector<Edge> &edges);
+
+    // returns true if the graph is planar; otherwise extracts up to
+    // `max_witnesses` Kuratowski subgraphs (0 for as many as OGDF finds),
+    // witness `i` owning `witness_ids[witness_offsets[i]..witness_offsets[i + 1]]`.
+    // Seeded runs (`random_dfs_tree`) are serialized with each other, as the
+    // seed goes to OGDF's process-wide random source
+    bool kuratowski_witnesses(
+        std::size_t n_vertices, const std::vector<Edge> &edges,
+        std::size_t max_witnesses, bool bundles,
+        bool random_dfs_tree, std::uint32_t seed, bool avoid_e2_minors,
+        std::vector<std::size_t> &witness_offsets,
+        std::vector<std::size_t> &witness_ids);
+
+    // returns true if the graph is planar, in which case `rotation` holds the
+    // cyclic order of incident input edge indices around each vertex, vertex
+    // `v` owning `rotation[rotation_offsets[v]..rotation_offsets[v + 1]]`;
+    // otherwise `witness_ids` receives a Kuratowski subgraph
+    bool planar_embedding(
+        std::size_t n_vertices, const std::vector<Edge> &edges,
+        std::vector<std::size_t> &rotation_offsets,
+        std::vector<std::size_t> &rotation,
+        std::vector<std::size_t> &witness_ids);
+
 } // namespace graph
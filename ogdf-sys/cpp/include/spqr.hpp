@@ -9,10 +9,48 @@
 namespace graph
 {
 
+  class PlanarSubgraphReader;
+
+  // Immutable addability mask shared with the `PlanarSubgraph` that
+  // published it: candidate `i` is bit `i % 64` of `words()[i / 64]`, and
+  // the bits past `len()` are zero.
+  class AddableSnapshot
+  {
+  public:
+    AddableSnapshot(const AddableSnapshot &) = delete;
+    AddableSnapshot &operator=(const AddableSnapshot &) = delete;
+    ~AddableSnapshot();
+
+    std::size_t len() const;
+    const std::vector<std::uint64_t> &words() const;
+
+  private:
+    friend class PlanarSubgraph;
+    friend class PlanarSubgraphReader;
+    AddableSnapshot(std::shared_ptr<const std::vector<std::uint64_t>> words, std::size_t len);
+    std::shared_ptr<const std::vector<std::uint64_t>> words_;
+    std::size_t len_;
+  };
+
+  // Every member function locks the shared state, so const members may be
+  // called from any number of threads while one thread calls the others.
   class PlanarSubgraph
   {
   public:
     using Mask = std::uint8_t;
+    // candidate states: required ones cannot be deselected or removed, and
+    // forbidden ones are never addable and cannot be selected
+    static constexpr std::uint8_t FREE = 0;
+    static constexpr std::uint8_t REQUIRED = 1;
+    static constexpr std::uint8_t FORBIDDEN = 2;
+    // outcomes of `check_set`, `check_commit` and `contract`
+    static constexpr std::uint8_t OK = 0;
+    static constexpr std::uint8_t IS_REQUIRED = 1;
+    static constexpr std::uint8_t IS_FORBIDDEN = 2;
+    static constexpr std::uint8_t NOT_ADDABLE = 3;
+    static constexpr std::uint8_t NOT_PLANAR = 4;
+    static constexpr std::uint8_t NO_TRANSACTION = 5;
+    static constexpr std::uint8_t NOT_SELECTED = 6;
 
     PlanarSubgraph(std::size_t num_verts,
                    const std::vector<Edge> &edges_all,
@@ -24,12 +62,179 @@ namespace graph
     PlanarSubgraph &operator=(PlanarSubgraph &&) noexcept;
     ~PlanarSubgraph();
 
+    // independent copy sharing the candidate list copy-on-write; its graph
+    // and caches are rebuilt lazily; throws during a transaction
+    std::unique_ptr<PlanarSubgraph> fork() const;
+    // read-only handle on this same state, usable from other threads
+    std::unique_ptr<PlanarSubgraphReader> reader() const;
+
+    // vertex ids handed out so far, including removed vertices
+    std::size_t num_verts() const;
+    bool is_vertex(std::size_t v) const;
+    // ids handed out so far, including removed candidates
+    std::size_t num_edges() const;
+    // every candidate by id, removed ones included
+    std::vector<Edge> edges() const;
+    // a single entry of `edges`; out-of-range ids throw
+    Edge edge(std::size_t edge_id) const;
+    bool is_candidate(std::size_t edge_id) const;
+    // as recorded, so including the changes of an open transaction
+    bool is_selected(std::size_t edge_id) const;
+    bool in_transaction() const;
+
+    // out-of-range ids throw
+    std::uint8_t state(std::size_t edge_id) const;
+    std::size_t num_required() const;
+    // whether `set(edge_id, present)` is allowed: it must respect the
+    // candidate's state and, with no transaction open, select a candidate
+    // that is not addable only if no required candidate lies in a block it
+    // would merge. One lock, and at most one addability test
+    std::uint8_t check_set(std::size_t edge_id, bool present) const;
+    // whether `commit` is allowed: a transaction must be open, and every
+    // block of the selection holding a required candidate must be planar
+    std::uint8_t check_commit() const;
+    // making a candidate required selects it, and returns false (changing
+    // nothing) if it is neither selected nor addable; making it forbidden
+    // deselects it; throws on a bad id or state, or during a transaction
+    bool set_state(std::size_t edge_id, std::uint8_t state);
+    // `set_state`, also filling `flipped` as `set_and_diff` does
+    bool set_state_and_diff(std::size_t edge_id, std::uint8_t state, std::vector<std::size_t> &flipped);
+    // whether the selection, as recorded, is planar
+    bool is_planar() const;
+
+    // ids are stable: removal leaves a tombstone and new candidates are
+    // appended; both throw while a transaction is open
+    std::size_t add_candidate(const Edge &e);
+    void remove_candidate(std::size_t edge_id);
+    // `remove_candidate`, filling the result as `set_and_diff` does
+    std::vector<std::size_t> remove_candidate_and_diff(std::size_t edge_id);
+    // vertex ids are stable in the same way; removing a vertex removes its
+    // incident candidates too, returning their ids in increasing order
+    std::size_t add_vertex();
+    std::vector<std::size_t> remove_vertex(std::size_t v);
+    // `remove_vertex`, also filling `flipped` as `set_and_diff` does
+    std::vector<std::size_t> remove_vertex_and_diff(std::size_t v, std::vector<std::size_t> &flipped);
+    // contracts selected candidate `edge_id`, merging its higher endpoint
+    // into the lower one; only the parallels and loops this makes are
+    // merged or dropped. `vertex_map[v]` and `candidate_map[i]` give where
+    // each id went, `SIZE_MAX` for candidates that are gone. Leaves
+    // everything alone and returns NOT_SELECTED unless `edge_id` is
+    // selected, or IS_REQUIRED, with `candidate_map` holding just the
+    // culprit, if a required candidate would be dropped as a loop; throws on
+    // a bad id or during a transaction
+    std::uint8_t contract(std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                          std::vector<std::size_t> &candidate_map);
+    // `contract`, also filling `flipped` as `set_and_diff` does
+    std::uint8_t contract_and_diff(std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                                   std::vector<std::size_t> &candidate_map, std::vector<std::size_t> &flipped);
+
+    // callers are expected to validate `edge_id`; out-of-range ids throw
     void set(std::size_t edge_id, bool present);
+    // `set`, returning the candidates whose addability flipped, in
+    // increasing order; empty during a transaction. Evaluates, before and
+    // after, only the candidates whose block-cut path crosses a block the
+    // update may change, or that it may connect
+    std::vector<std::size_t> set_and_diff(std::size_t edge_id, bool present);
+    // selects each candidate of `order` that is still addable when reached,
+    // returning those selected in that order; throws on a bad id or during a
+    // transaction. Works in batches, each one planarity test and one update
+    std::vector<std::size_t> fill_greedy(const std::vector<std::size_t> &order);
+    // `fill_greedy`, also filling `flipped` as `set_and_diff` does
+    std::vector<std::size_t> fill_greedy_and_diff(const std::vector<std::size_t> &order,
+                                                  std::vector<std::size_t> &flipped);
     std::vector<std::uint8_t> query() const;
+    // the same mask without copying it
+    std::unique_ptr<AddableSnapshot> snapshot() const;
+
+    // addability is evaluated on demand: updates only mark the affected
+    // candidates stale, and these pay for just the ids they are asked about
+    bool is_addable(std::size_t edge_id) const;
+    std::vector<std::uint8_t> addable_among(const std::vector<std::size_t> &edge_ids) const;
+    // test instrumentation, not part of the API proper: how many times a
+    // candidate's answer has been worked out through the block-cut forest,
+    // as opposed to reused or settled by a cheap case
+    std::size_t evaluations() const;
+
+    // fills `witness_ids` with a Kuratowski subgraph made of `edge_id` and
+    // selected candidates, and returns true, iff `edge_id` is an unselected
+    // candidate that is not addable
+    bool obstruction(std::size_t edge_id, std::vector<std::size_t> &witness_ids) const;
+
+    // whether an edge between vertices `u` and `v`, declared or not, could
+    // join the selection; out-of-range vertices throw
+    bool can_add(std::size_t u, std::size_t v) const;
+    // every other vertex `w` with `can_add(v, w)`, in increasing order
+    std::vector<std::size_t> addable_partners(std::size_t v) const;
+
+    // whether a new vertex adjacent to every one of `verts` could join the
+    // selection, i.e. whether they can share a face; out-of-range or removed
+    // vertices throw
+    bool can_attach(const std::vector<std::size_t> &verts) const;
+    // like `embedding`, but with `verts` on a common face; false if there is
+    // no such embedding
+    bool attach_embedding(const std::vector<std::size_t> &verts, std::vector<std::size_t> &rotation_offsets,
+                          std::vector<std::size_t> &rotation) const;
+
+    // rotation system of the selected candidates in the layout of
+    // `planar_embedding`, entries being candidate ids; false (and empty
+    // output) if the selection is not planar. Computed afresh by
+    // Boyer-Myrvold, not taken from the SPQR trees behind `is_addable`
+    bool embedding(std::vector<std::size_t> &rotation_offsets, std::vector<std::size_t> &rotation) const;
+    // like `embedding`, but chosen so that addable candidate `edge_id` has
+    // both endpoints on a common face; false if it is not addable
+    bool witness_embedding(std::size_t edge_id, std::vector<std::size_t> &rotation_offsets,
+                           std::vector<std::size_t> &rotation) const;
+
+    // addable candidates that selecting `edge_id` would make non-addable;
+    // empty unless `edge_id` is addable itself. Leaves the selection alone:
+    // builds one SPQR tree over the blocks `edge_id` would merge and walks
+    // the block-cut path of each candidate in its components
+    std::vector<std::size_t> conflicts(std::size_t edge_id) const;
+    // whether the selection plus candidates `edge_ids` is planar; if not,
+    // fills `conflict` with an inclusion-minimal subset of `edge_ids` that
+    // is not. Runs on a copy, leaving the selection and caches alone;
+    // out-of-range ids throw
+    bool can_add_all(const std::vector<std::size_t> &edge_ids, std::vector<std::size_t> &conflict) const;
+    // `conflicts` of every candidate, as adjacency lists `neighbours[
+    // offsets[i]..offsets[i + 1]]`; one `conflicts` per addable candidate
+    void conflict_graph(std::vector<std::size_t> &offsets, std::vector<std::size_t> &neighbours) const;
+
+    // while a transaction is open, `set` only records the selection and
+    // queries keep answering for the state at `begin`; each call returns
+    // false if a transaction is already open (`begin`) or not open
+    bool begin();
+    bool commit();
+    bool rollback();
+    // `commit`, filling `flipped` as `set_and_diff` does
+    bool commit_and_diff(std::vector<std::size_t> &flipped);
 
   private:
+    friend class PlanarSubgraphReader;
     struct Impl;
-    std::unique_ptr<Impl> impl;
+    explicit PlanarSubgraph(std::unique_ptr<Impl> impl);
+    std::shared_ptr<Impl> impl;
+  };
+
+  // Keeps the state alive after its `PlanarSubgraph` is dropped, and sees
+  // each update as soon as the writer's call returns.
+  class PlanarSubgraphReader
+  {
+  public:
+    PlanarSubgraphReader(const PlanarSubgraphReader &) = delete;
+    PlanarSubgraphReader &operator=(const PlanarSubgraphReader &) = delete;
+    ~PlanarSubgraphReader();
+
+    std::size_t num_edges() const;
+    bool is_candidate(std::size_t edge_id) const;
+    std::vector<std::uint8_t> query() const;
+    std::unique_ptr<AddableSnapshot> snapshot() const;
+    // out-of-range ids throw
+    bool is_addable(std::size_t edge_id) const;
+
+  private:
+    friend class PlanarSubgraph;
+    explicit PlanarSubgraphReader(std::shared_ptr<PlanarSubgraph::Impl> impl);
+    std::shared_ptr<PlanarSubgraph::Impl> impl;
   };
 
 } // namespace graph
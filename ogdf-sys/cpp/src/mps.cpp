@@ -6,6 +6,7 @@
 #endif
 #include <ogdf/basic/Graph.h>
 #include <ogdf/basic/SList.h>
+#include <ogdf/basic/basic.h>
 #include <ogdf/planarity/BoyerMyrvold.h>
 #if defined(__GNUC__)
 #pragma GCC diagnostic pop
@@ -13,9 +14,11 @@
 
 #include <algorithm>
 #include <limits>
+#include <map>
 #include <memory>
+#include <mutex>
 #include <stdexcept>
-#include <unordered_set>
+#include <utility>
 #include <vector>
 
 namespace graph
@@ -23,73 +26,199 @@ namespace graph
 
     using namespace ogdf;
 
-    std::vector<Edge> boyer_myrvold_witness(
-        std::size_t n_vertices, const std::vector<Edge> &edges)
+    namespace
     {
-        Graph G;
-        std::vector<node> nodes(n_vertices);
-        for (size_t i = 0; i < n_vertices; ++i)
-            nodes[i] = G.newNode();
-        for (const auto &e : edges)
+
+        // OGDF only sees the underlying simple graph; each vertex pair is
+        // represented by the first input edge joining it, and parallel
+        // copies and loops are tracked on the side.
+        struct SimpleGraph
         {
-            if (e.u >= n_vertices || e.v >= n_vertices)
+            Graph G;
+            std::vector<node> nodes;
+            EdgeArray<size_t> edge_id;
+            EdgeArray<std::vector<size_t>> parallels;
+            std::vector<std::vector<size_t>> loops;
+
+            SimpleGraph(std::size_t n_vertices, const std::vector<Edge> &edges)
+                : nodes(n_vertices), edge_id(G, 0), parallels(G), loops(n_vertices)
             {
-                throw std::out_of_range("edge endpoint out of range");
+                for (size_t i = 0; i < n_vertices; ++i)
+                    nodes[i] = G.newNode();
+
+                std::map<std::pair<size_t, size_t>, edge> rep;
+                for (size_t i = 0; i < edges.size(); ++i)
+                {
+                    const auto &e = edges[i];
+                    if (e.u >= n_vertices || e.v >= n_vertices)
+                    {
+                        throw std::out_of_range("edge endpoint out of range");
+                    }
+                    if (e.u == e.v)
+                    {
+                        loops[e.u].push_back(i);
+                        continue;
+                    }
+                    auto it = rep.find(std::minmax(e.u, e.v));
+                    if (it != rep.end())
+                    {
+                        parallels[it->second].push_back(i);
+                        continue;
+                    }
+                    edge eG = G.newEdge(nodes[e.u], nodes[e.v]);
+                    edge_id[eG] = i;
+                    rep.emplace(std::minmax(e.u, e.v), eG);
+                }
             }
-            if (e.u == e.v)
+
+            std::vector<size_t> witness_ids(const KuratowskiWrapper &kw) const
             {
-                continue;
+                std::vector<size_t> out;
+                for (edge e : kw.edgeList)
+                {
+                    if (e == nullptr)
+                        continue;
+                    out.push_back(edge_id[e]);
+                }
+                std::sort(out.begin(), out.end());
+                out.erase(std::unique(out.begin(), out.end()), out.end());
+                return out;
             }
-            G.newEdge(
-                nodes[static_cast<size_t>(e.u)],
-                nodes[static_cast<size_t>(e.v)]);
+        };
+
+    } // namespace
+
+    std::vector<Edge> boyer_myrvold_witness(
+        std::size_t n_vertices, const std::vector<Edge> &edges)
+    {
+        std::vector<Edge> witness;
+        for (size_t i : boyer_myrvold_witness_ids(n_vertices, edges))
+        {
+            witness.push_back(Edge{std::min(edges[i].u, edges[i].v), std::max(edges[i].u, edges[i].v)});
+        }
+        std::sort(witness.begin(), witness.end(), [](const Edge &a, const Edge &b)
+                  { return (a.u < b.u) || (a.u == b.u && a.v < b.v); });
+        return witness;
+    }
+
+    std::vector<std::size_t> boyer_myrvold_witness_ids(
+        std::size_t n_vertices, const std::vector<Edge> &edges)
+    {
+        std::vector<std::size_t> offsets;
+        std::vector<std::size_t> witness;
+        kuratowski_witnesses(
+            n_vertices, edges, 1,
+            /*bundles=*/false,
+            /*random_dfs_tree=*/false, 0,
+            /*avoid_e2_minors=*/true,
+            offsets, witness);
+        return witness;
+    }
+
+    bool kuratowski_witnesses(
+        std::size_t n_vertices, const std::vector<Edge> &edges,
+        std::size_t max_witnesses, bool bundles,
+        bool random_dfs_tree, std::uint32_t seed, bool avoid_e2_minors,
+        std::vector<std::size_t> &witness_offsets,
+        std::vector<std::size_t> &witness_ids)
+    {
+        witness_offsets.assign(1, 0);
+        witness_ids.clear();
+
+        SimpleGraph S(n_vertices, edges);
+
+        // the seed lands in OGDF's process-wide random source, so seeded
+        // runs hold this until their DFS tree has been drawn
+        static std::mutex seed_mu;
+        std::unique_lock<std::mutex> seeded(seed_mu, std::defer_lock);
+        if (random_dfs_tree)
+        {
+            seeded.lock();
+            setSeed(static_cast<int>(seed));
         }
+        const int grade = max_witnesses
+                              ? static_cast<int>(std::min<std::size_t>(max_witnesses, std::numeric_limits<int>::max()))
+                              : -1;
 
         BoyerMyrvold bm;
         SList<KuratowskiWrapper> witnesses;
         bool planar = bm.planarEmbedDestructive(
-            G, witnesses, 1,
+            S.G, witnesses, grade,
+            bundles,
+            /*limitStructures=*/true,
+            random_dfs_tree,
+            avoid_e2_minors);
+
+        if (planar)
+            return true;
+
+        for (const KuratowskiWrapper &kw : witnesses)
+        {
+            auto w = S.witness_ids(kw);
+            witness_ids.insert(witness_ids.end(), w.begin(), w.end());
+            witness_offsets.push_back(witness_ids.size());
+        }
+        return false;
+    }
+
+    bool planar_embedding(
+        std::size_t n_vertices, const std::vector<Edge> &edges,
+        std::vector<std::size_t> &rotation_offsets,
+        std::vector<std::size_t> &rotation,
+        std::vector<std::size_t> &witness_ids)
+    {
+        rotation_offsets.assign(n_vertices + 1, 0);
+        rotation.clear();
+        witness_ids.clear();
+
+        SimpleGraph S(n_vertices, edges);
+
+        BoyerMyrvold bm;
+        SList<KuratowskiWrapper> witnesses;
+        bool planar = bm.planarEmbed(
+            S.G, witnesses, 1,
             /*bundles=*/false,
             /*limitStructures=*/true,
             /*randomDFSTree=*/false,
             /*avoidE2Minors=*/true);
 
-        if (planar)
-            return std::vector<Edge>();
-        if (witnesses.empty())
-            return std::vector<Edge>();
-
-        NodeArray<size_t> idx(G, -1);
-        for (size_t i = 0; i < nodes.size(); ++i)
-            idx[nodes[i]] = i;
-        const KuratowskiWrapper &kw = witnesses.front();
-        std::unordered_set<long long> seen;
-        auto key = [](int a, int b)
+        if (!planar)
         {
-            if (a > b)
-                std::swap(a, b);
-            return (static_cast<long long>(a) << 32) | static_cast<unsigned long long>(b);
-        };
-        std::vector<Edge> witness_edges;
-        for (edge e : kw.edgeList)
+            if (!witnesses.empty())
+            {
+                witness_ids = S.witness_ids(witnesses.front());
+            }
+            return false;
+        }
+
+        for (size_t v = 0; v < n_vertices; ++v)
         {
-            if (e == nullptr)
-                continue;
-            size_t u = idx[e->source()];
-            size_t v = idx[e->target()];
-            if (u == v)
-                continue;
-            if (u > v)
-                std::swap(u, v);
-            auto k = key(u, v);
-            if (seen.count(k))
-                continue;
-            seen.insert(k);
-            witness_edges.push_back(Edge{u, v});
+            rotation_offsets[v] = rotation.size();
+            for (adjEntry a = S.nodes[v]->firstAdj(); a; a = a->succ())
+            {
+                edge eG = a->theEdge();
+                const auto &par = S.parallels[eG];
+                // parallel copies nest alongside their representative, so
+                // they appear in opposite orders at the two endpoints
+                if (a->isSource())
+                {
+                    rotation.push_back(S.edge_id[eG]);
+                    rotation.insert(rotation.end(), par.begin(), par.end());
+                }
+                else
+                {
+                    rotation.insert(rotation.end(), par.rbegin(), par.rend());
+                    rotation.push_back(S.edge_id[eG]);
+                }
+            }
+            for (size_t i : S.loops[v])
+            {
+                rotation.push_back(i);
+                rotation.push_back(i);
+            }
         }
-        std::sort(witness_edges.begin(), witness_edges.end(), [](const Edge &a, const Edge &b)
-                  { return (a.u < b.u) || (a.u == b.u && a.v < b.v); });
-        return witness_edges;
+        rotation_offsets[n_vertices] = rotation.size();
+        return true;
     }
 
 } // namespace graph
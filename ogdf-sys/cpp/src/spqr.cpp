@@ -1,4 +1,5 @@
 #include "spqr.hpp"
+#include "mps.hpp"
 
 #if defined(__GNUC__)
 #pragma GCC diagnostic push
@@ -7,9 +8,8 @@
 #include <ogdf/basic/CombinatorialEmbedding.h>
 #include <ogdf/basic/Graph.h>
 #include <ogdf/basic/Graph_d.h>
-#include <ogdf/basic/simple_graph_alg.h>
-#include <ogdf/decomposition/BCTree.h>
 #include <ogdf/decomposition/StaticPlanarSPQRTree.h>
+#include <ogdf/planarity/BoyerMyrvold.h>
 #if defined(__GNUC__)
 #pragma GCC diagnostic pop
 #endif
@@ -20,284 +20,1285 @@
 #include <cstdint>
 #include <memory>
 #include <mutex>
+#include <map>
 #include <optional>
+#include <set>
 #include <stdexcept>
 #include <unordered_map>
+#include <unordered_set>
 #include <vector>
 
 namespace graph
 {
 
-    struct BCTreeX : ogdf::BCTree
-    {
-        using ogdf::BCTree::BCTree;
-        using ogdf::BCTree::findNCA;
-        using ogdf::BCTree::parent;
-    };
-
     using Mask = PlanarSubgraph::Mask;
     struct BlockInfo;
 
-    struct PlanarSubgraph::Impl
-    {
-        Impl(std::size_t num_verts,
-             const std::vector<Edge> &edges_all,
-             const std::vector<std::uint8_t> &edges_added_init);
-
-        Impl(const Impl &) = delete;
-        Impl &operator=(const Impl &) = delete;
-        Impl(Impl &&) = delete;
-        Impl &operator=(Impl &&) = delete;
-        ~Impl();
-
-        void set(std::size_t edge_id, bool present);
-        std::vector<std::uint8_t> query() const;
-
-    private:
-        void recalculate_addable(const std::vector<std::uint8_t> &added_snapshot);
-        bool can_add_along_bc(const BCTreeX &bc, ogdf::node uG, ogdf::node vG);
-        BlockInfo &get_or_build_block(const BCTreeX &bc, ogdf::node vB);
-        void reset_blocks(const ogdf::Graph &bc_tree);
-
-        const std::size_t num_verts;
-        const std::vector<Edge> edges_all;
-
-        std::vector<std::uint8_t> edges_added;
-        mutable std::shared_ptr<const std::vector<std::uint8_t>> addable_snap;
-        mutable std::mutex mu;
-
-        ogdf::Graph G;
-        std::vector<ogdf::node> v_g;
-        std::vector<ogdf::edge> cur_edges;
-        std::unique_ptr<ogdf::NodeArray<BlockInfo *>> blocks;
-        std::vector<std::unique_ptr<BlockInfo>> owned_blocks;
-    };
-
-    inline std::pair<ogdf::face, ogdf::face> faces_of(const ogdf::ConstCombinatorialEmbedding &CE, ogdf::adjEntry a)
-    {
-        return {CE.leftFace(a), CE.rightFace(a)};
-    }
-
-    inline std::pair<ogdf::face, ogdf::face> faces_of(const ogdf::ConstCombinatorialEmbedding &CE, ogdf::edge e)
-    {
-        return faces_of(CE, e->adjSource());
-    }
-
-    inline bool contains_face_id(const std::vector<uint32_t> &v, uint32_t id)
-    {
-        return std::binary_search(v.begin(), v.end(), id);
-    }
-
-    inline ogdf::edge skel_edge_at(const ogdf::StaticPlanarSPQRTree &spqr, ogdf::edge e_t, ogdf::node v_t)
-    {
-        return (e_t->source() == v_t) ? spqr.skeletonEdgeSrc(e_t) : spqr.skeletonEdgeTgt(e_t);
-    }
-
-    struct BlockInfo
+    // Block-cut forest of a simple graph on vertex ids, kept up to date edge
+    // by edge instead of being rebuilt. Each tree hangs from a root block;
+    // every other block hangs from the cut vertex it shares with the block
+    // above it. A vertex with edges has a `home`, the block nearest the root
+    // holding it, and lies in that block and in those hanging from it.
+    // Nothing stores depths: paths are found by climbing from both ends.
+    //
+    // An edge within a block grows it, one closing a cycle through several
+    // blocks merges them into the largest, and one between two trees hangs
+    // the smaller tree from a new bridge block, so insertions cost
+    // O(m log n) overall besides their path walks. Deleting edges recomputes
+    // just the blocks that held them, and a tree falling apart keeps its
+    // label for its largest piece. Retired block ids are reused.
+    class BlockForest
     {
-        ogdf::Graph block;
-        ogdf::NodeArray<ogdf::node> h_to_b;
-
-        std::unique_ptr<ogdf::StaticPlanarSPQRTree> spqr;
-        const ogdf::Graph *tree = nullptr;
-
-        ogdf::NodeArray<ogdf::node> parent_t;
-        ogdf::NodeArray<ogdf::edge> parent_edge_t;
-        ogdf::NodeArray<int> depth_t;
-        std::vector<ogdf::NodeArray<ogdf::node>> up;
-        ogdf::NodeArray<int> tin, tout;
-
-        ogdf::NodeArray<ogdf::node> repr_t;
-
-        ogdf::NodeArray<std::optional<ogdf::ConstCombinatorialEmbedding>> emb_r;
+    public:
+        static constexpr std::size_t NONE = SIZE_MAX;
+        using VertexPair = std::pair<std::size_t, std::size_t>;
+
+        // empty `edges` marks a retired id
+        struct Block
+        {
+            // as (smaller, larger) endpoint pairs
+            std::vector<VertexPair> edges;
+            std::vector<std::size_t> verts;
+            // the cut vertex it hangs from, NONE for the root of a tree, and
+            // where it sits in that vertex's `hanging` list
+            std::size_t parent = NONE;
+            std::size_t slot = 0;
+        };
 
-        struct FaceIndex
+        // a block on a path, entered at `x` and left at `y`
+        struct Step
         {
-            std::unordered_map<size_t, uint32_t> face_id;
-            std::unordered_map<size_t, std::vector<uint32_t>> v_faces;
-            std::unordered_map<size_t, std::array<uint32_t, 2>> e_faces;
-            uint32_t next_id = 0;
+            std::size_t block;
+            std::size_t x;
+            std::size_t y;
         };
-        ogdf::NodeArray<FaceIndex *> r_face_index;
-        std::vector<std::unique_ptr<FaceIndex>> r_face_owned;
 
-        struct PairKey
+        // what an update did: the ids it retired, and those of blocks that
+        // are new or took on another edge set, which may reuse retired ids
+        struct Change
         {
-            ogdf::node u{nullptr}, v{nullptr};
+            std::vector<std::size_t> retired;
+            std::vector<std::size_t> changed;
         };
-        struct PairKeyHash
+
+        // starts over with `n` vertices and `edges`, reporting nothing
+        void build(std::size_t n, const std::vector<VertexPair> &edges);
+        std::size_t add_vertex();
+        // the edge must be new, and `u != v`
+        void insert(std::size_t u, std::size_t v, Change &change);
+        // every edge must be there
+        void erase(const std::vector<VertexPair> &edges, Change &change);
+
+        std::size_t block_ids() const { return blocks.size(); }
+        const Block &block(std::size_t b) const { return blocks[b]; }
+        // the same for two vertices iff they are connected
+        std::size_t component(std::size_t v) const { return comp[v]; }
+        // the block holding both vertices, NONE if there is none
+        std::size_t common_block(std::size_t u, std::size_t v) const;
+        // the blocks on the path from `u` to `v`, in order, each entered and
+        // left by the endpoint itself at either end and by the cut vertex it
+        // shares with its neighbour elsewhere; empty unless the two are
+        // distinct and connected
+        std::vector<Step> path(std::size_t u, std::size_t v) const;
+
+    private:
+        // a block, or a cut vertex standing for its C-node
+        struct Node
         {
-            size_t operator()(const PairKey &k) const noexcept
-            {
-                size_t a = (size_t)(uintptr_t)k.u;
-                size_t b = (size_t)(uintptr_t)k.v;
-                if (a > b)
-                {
-                    std::swap(a, b);
-                }
-                return a * 1315423911u ^ (b + 0x9e3779b97f4a7c15ull + (a << 6) + (a >> 2));
-            }
+            bool is_block;
+            std::size_t id;
         };
-        struct PairKeyEq
+        // depth-first walk over one tree, vertices and blocks alike
+        struct Walk
         {
-            bool operator()(const PairKey &x, const PairKey &y) const noexcept
-            {
-                size_t ax = (size_t)(uintptr_t)x.u, bx = (size_t)(uintptr_t)x.v;
-                size_t ay = (size_t)(uintptr_t)y.u, by = (size_t)(uintptr_t)y.v;
-                if (ax > bx)
-                {
-                    std::swap(ax, bx);
-                }
-                if (ay > by)
-                {
-                    std::swap(ay, by);
-                }
-                return ax == ay && bx == by;
-            }
+            std::vector<std::pair<Node, std::size_t>> stack;
+            std::vector<std::size_t> verts;
         };
-        mutable std::unordered_map<PairKey, bool, PairKeyHash, PairKeyEq> memo;
 
-        BlockInfo(const ogdf::BCTree &bc, ogdf::node vB)
-        {
-            const ogdf::Graph &H = bc.auxiliaryGraph();
+        std::vector<std::vector<VertexPair>> biconnected(const std::vector<VertexPair> &edges) const;
+        Node start(std::size_t v) const;
+        bool up(Node &n) const;
+        std::uint64_t &mark(Node n) const;
+        std::vector<Node> climb(std::size_t u, std::size_t v, std::size_t &top) const;
+        std::size_t degree(Node n) const;
+        Node neighbour(Node n, std::size_t k) const;
+        void visit(Walk &walk, Node n, std::uint64_t s) const;
+        bool step(Walk &walk, std::uint64_t s) const;
+        std::size_t new_block(Change &change);
+        void retire(std::size_t b, Change &change);
+        void hang(std::size_t b, std::size_t p);
+        void unhang(std::size_t b);
+        void reroot(std::size_t v);
+        void link(std::size_t u, std::size_t v, Change &change);
+        void merge(const std::vector<Node> &seq, std::size_t top, Change &change);
+        std::vector<std::size_t> place(std::vector<std::vector<VertexPair>> pieces, std::size_t anchor,
+                                       const std::vector<std::size_t> &verts, Change &change);
+        void recompute(std::size_t b, Change &change);
+        void split(const std::vector<std::size_t> &starts);
+
+        std::vector<Block> blocks;
+        std::vector<std::size_t> free_ids;
+        std::vector<std::size_t> home;
+        std::vector<std::vector<std::size_t>> hanging;
+        // a label per vertex, and the size of each label's tree
+        std::vector<std::size_t> comp;
+        std::vector<std::size_t> comp_size;
+        // scratch marks, stamped so they never need clearing, and scratch
+        // indices valid for the vertices carrying the latest stamp
+        mutable std::vector<std::uint64_t> vmark, bmark;
+        mutable std::vector<std::size_t> local;
+        mutable std::uint64_t stamp = 0;
+    };
 
-            h_to_b.init(H, nullptr);
-            for (ogdf::edge eH : bc.hEdges(vB))
+    // Blocks of the graph `edges`, each as its list of edges; iterative, so
+    // long paths cannot overflow the stack.
+    std::vector<std::vector<BlockForest::VertexPair>> BlockForest::biconnected(const std::vector<VertexPair> &edges) const
+    {
+        const std::uint64_t s = ++stamp;
+        std::size_t n = 0;
+        for (auto [x, y] : edges)
+        {
+            for (std::size_t w : {x, y})
             {
-                ogdf::node xH = eH->source(), yH = eH->target();
-                if (!h_to_b[xH])
-                {
-                    h_to_b[xH] = block.newNode();
-                }
-                if (!h_to_b[yH])
+                if (vmark[w] != s)
                 {
-                    h_to_b[yH] = block.newNode();
+                    vmark[w] = s;
+                    local[w] = n++;
                 }
-                block.newEdge(h_to_b[xH], h_to_b[yH]);
             }
+        }
+        // neighbours of local vertex `x`, with the edge leading there, are
+        // `adj[first[x]..first[x + 1]]`
+        std::vector<std::size_t> first(n + 1, 0);
+        for (auto [x, y] : edges)
+        {
+            ++first[local[x] + 1];
+            ++first[local[y] + 1];
+        }
+        for (size_t x = 0; x < n; ++x)
+        {
+            first[x + 1] += first[x];
+        }
+        std::vector<std::size_t> next(first.begin(), first.end() - 1);
+        std::vector<std::pair<std::size_t, std::size_t>> adj(2 * edges.size());
+        for (size_t e = 0; e < edges.size(); ++e)
+        {
+            const std::size_t a = local[edges[e].first], b = local[edges[e].second];
+            adj[next[a]++] = {b, e};
+            adj[next[b]++] = {a, e};
+        }
+        std::copy(first.begin(), first.end() - 1, next.begin());
 
-            const int nv = block.numberOfNodes();
-            const int ne = block.numberOfEdges();
-            if (nv < 2 || (nv == 2 && ne < 3))
+        std::vector<std::size_t> disc(n, NONE), low(n, 0), via(n, NONE);
+        std::vector<std::size_t> dfs, pending;
+        std::vector<std::vector<VertexPair>> out;
+        std::size_t clock = 0;
+        for (size_t r = 0; r < n; ++r)
+        {
+            if (disc[r] != NONE)
             {
-                static ogdf::Graph k_empty_graph;
-                spqr = nullptr;
-                tree = &k_empty_graph;
-                repr_t.init(block, nullptr);
-                emb_r.init(k_empty_graph);
-                return;
+                continue;
             }
-
-            spqr = std::make_unique<ogdf::StaticPlanarSPQRTree>(block, false);
-            tree = &spqr->tree();
-
-            repr_t.init(block, nullptr);
-            for (ogdf::node vT = tree->firstNode(); vT; vT = vT->succ())
+            disc[r] = low[r] = clock++;
+            dfs.push_back(r);
+            while (!dfs.empty())
             {
-                const ogdf::Skeleton &Sk = spqr->skeleton(vT);
-                for (ogdf::node xM = Sk.getGraph().firstNode(); xM; xM = xM->succ())
+                const std::size_t x = dfs.back();
+                if (next[x] < first[x + 1])
                 {
-                    ogdf::node orig = Sk.original(xM);
-                    if (!repr_t[orig])
+                    auto [y, e] = adj[next[x]++];
+                    if (e == via[x])
                     {
-                        repr_t[orig] = vT;
+                        continue;
+                    }
+                    if (disc[y] == NONE)
+                    {
+                        via[y] = e;
+                        disc[y] = low[y] = clock++;
+                        pending.push_back(e);
+                        dfs.push_back(y);
                     }
+                    else if (disc[y] < disc[x])
+                    {
+                        pending.push_back(e);
+                        low[x] = std::min(low[x], disc[y]);
+                    }
+                    continue;
+                }
+                dfs.pop_back();
+                if (dfs.empty())
+                {
+                    break;
+                }
+                const std::size_t p = dfs.back();
+                low[p] = std::min(low[p], low[x]);
+                if (low[x] >= disc[p])
+                {
+                    out.emplace_back();
+                    std::size_t e;
+                    do
+                    {
+                        e = pending.back();
+                        pending.pop_back();
+                        out.back().push_back(edges[e]);
+                    } while (e != via[x]);
                 }
             }
-            emb_r.init(*tree);
-            r_face_index.init(*tree, nullptr);
-            build_lca();
         }
+        return out;
+    }
 
-        ogdf::ConstCombinatorialEmbedding &embedding_r(ogdf::node vT)
+    void BlockForest::build(const std::size_t n, const std::vector<VertexPair> &edges)
+    {
+        blocks.clear();
+        free_ids.clear();
+        bmark.clear();
+        home.assign(n, NONE);
+        hanging.assign(n, {});
+        vmark.assign(n, 0);
+        local.assign(n, 0);
+        comp.assign(n, 0);
+        comp_size.assign(n, 0);
+
+        std::vector<VertexPair> sorted;
+        for (auto [u, v] : edges)
         {
-            auto &slot = emb_r[vT];
-            if (!slot)
-            {
-                const ogdf::Skeleton &S = spqr->skeleton(vT);
-                slot = ogdf::ConstCombinatorialEmbedding(S.getGraph());
-            }
-            return *slot;
+            sorted.emplace_back(std::min(u, v), std::max(u, v));
         }
-
-        uint32_t fi_get_or_assign(ogdf::node vT, ogdf::face f)
+        std::vector<std::size_t> all(n);
+        for (size_t v = 0; v < n; ++v)
         {
-            auto &FI = *r_face_index[vT];
-            size_t key = (size_t)(uintptr_t)f;
-            auto it = FI.face_id.find(key);
-            if (it != FI.face_id.end())
-            {
-                return it->second;
-            }
-            uint32_t id = FI.next_id++;
-            FI.face_id.emplace(key, id);
-            return id;
+            all[v] = v;
         }
-
-        void ensure_face_index(ogdf::node vT)
+        Change ignored;
+        const std::uint64_t s = ++stamp;
+        for (std::size_t root : place(biconnected(sorted), NONE, all, ignored))
         {
-            if (spqr->typeOf(vT) != ogdf::SPQRTree::NodeType::RNode)
+            Walk walk;
+            visit(walk, Node{false, root}, s);
+            while (step(walk, s))
             {
-                return;
             }
-            if (r_face_index[vT])
+            for (std::size_t w : walk.verts)
             {
-                return;
+                comp[w] = root;
             }
-            r_face_owned.emplace_back(std::make_unique<BlockInfo::FaceIndex>());
-            r_face_index[vT] = r_face_owned.back().get();
+            comp_size[root] = walk.verts.size();
         }
+    }
 
-        const std::vector<uint32_t> &vertex_face_ids(ogdf::node vT, ogdf::node bB)
+    std::size_t BlockForest::add_vertex()
+    {
+        home.push_back(NONE);
+        hanging.emplace_back();
+        vmark.push_back(0);
+        local.push_back(0);
+        comp.push_back(comp_size.size());
+        comp_size.push_back(1);
+        return home.size() - 1;
+    }
+
+    // A block holds a vertex iff it is the vertex's home or hangs from it.
+    // Two blocks share at most one vertex, so a block holding both hangs
+    // from neither or from one of them.
+    std::size_t BlockForest::common_block(const std::size_t u, const std::size_t v) const
+    {
+        const std::size_t a = home[u], b = home[v];
+        if (a != NONE && (a == b || blocks[a].parent == v))
         {
-            ensure_face_index(vT);
-            auto &FI = *r_face_index[vT];
-            size_t key = (size_t)(uintptr_t)bB;
-            auto it = FI.v_faces.find(key);
-            if (it != FI.v_faces.end())
-            {
-                return it->second;
-            }
+            return a;
+        }
+        if (b != NONE && blocks[b].parent == u)
+        {
+            return b;
+        }
+        return NONE;
+    }
 
-            const ogdf::Skeleton &S = spqr->skeleton(vT);
-            auto &CE = embedding_r(vT);
-            std::vector<uint32_t> vec;
-            for (ogdf::node xM = S.getGraph().firstNode(); xM; xM = xM->succ())
+    BlockForest::Node BlockForest::start(const std::size_t v) const
+    {
+        return hanging[v].empty() ? Node{true, home[v]} : Node{false, v};
+    }
+
+    // Moves to the parent node; false at the root.
+    bool BlockForest::up(Node &n) const
+    {
+        if (!n.is_block)
+        {
+            n = Node{true, home[n.id]};
+            return true;
+        }
+        const std::size_t p = blocks[n.id].parent;
+        if (p == NONE)
+        {
+            return false;
+        }
+        n = Node{false, p};
+        return true;
+    }
+
+    std::uint64_t &BlockForest::mark(const Node n) const
+    {
+        return n.is_block ? bmark[n.id] : vmark[n.id];
+    }
+
+    // The nodes on the path from `u` to `v`, with `top` the index of the
+    // highest. Both ends climb in turn, marking what they pass, until one
+    // reaches a node the other has marked: the first such node is the
+    // lowest common ancestor, so neither end climbs past it by more than the
+    // other's distance to it.
+    std::vector<BlockForest::Node> BlockForest::climb(const std::size_t u, const std::size_t v, std::size_t &top) const
+    {
+        std::array<Node, 2> at{start(u), start(v)};
+        std::array<bool, 2> live{true, true};
+        std::array<std::vector<Node>, 2> seen;
+        const std::uint64_t base = stamp + 1;
+        stamp += 2;
+        while (live[0] || live[1])
+        {
+            for (int s = 0; s < 2; ++s)
             {
-                if (S.original(xM) != bB)
+                if (!live[s])
                 {
                     continue;
                 }
-                for (ogdf::adjEntry a = xM->firstAdj(); a; a = a->succ())
+                std::uint64_t &m = mark(at[s]);
+                if (m == base + 1 - s)
                 {
-                    auto ff = faces_of(CE, a);
-                    vec.push_back(fi_get_or_assign(vT, ff.first));
-                    vec.push_back(fi_get_or_assign(vT, ff.second));
+                    // `seen[1 - s]` passed the meeting node, `seen[s]` stops
+                    // short of it
+                    std::vector<Node> &other = seen[1 - s];
+                    size_t k = other.size();
+                    while (other[k - 1].is_block != at[s].is_block || other[k - 1].id != at[s].id)
+                    {
+                        --k;
+                    }
+                    other.resize(k);
+                    std::vector<Node> &from_u = s == 0 ? seen[0] : other;
+                    const std::vector<Node> &from_v = s == 0 ? other : seen[1];
+                    top = s == 0 ? from_u.size() : from_u.size() - 1;
+                    from_u.insert(from_u.end(), from_v.rbegin(), from_v.rend());
+                    return std::move(from_u);
                 }
+                m = base + s;
+                seen[s].push_back(at[s]);
+                live[s] = up(at[s]);
             }
-            std::sort(vec.begin(), vec.end());
-            vec.erase(std::unique(vec.begin(), vec.end()), vec.end());
-            auto [pos, _] = FI.v_faces.emplace(key, std::move(vec));
-            return pos->second;
         }
+        return {};
+    }
 
-        std::array<uint32_t, 2> edge_face_ids(ogdf::node vT, ogdf::edge eT)
+    std::vector<BlockForest::Step> BlockForest::path(const std::size_t u, const std::size_t v) const
+    {
+        if (u == v || comp[u] != comp[v])
         {
-            ensure_face_index(vT);
-            auto &FI = *r_face_index[vT];
-            size_t key = (size_t)(uintptr_t)eT;
-            auto it = FI.e_faces.find(key);
-            if (it != FI.e_faces.end())
+            return {};
+        }
+        std::size_t top;
+        const std::vector<Node> seq = climb(u, v, top);
+        std::vector<Step> out;
+        for (size_t k = 0; k < seq.size(); ++k)
+        {
+            if (seq[k].is_block)
             {
-                return it->second;
+                out.push_back({seq[k].id, k == 0 ? u : seq[k - 1].id, k + 1 == seq.size() ? v : seq[k + 1].id});
             }
+        }
+        return out;
+    }
 
-            auto &CE = embedding_r(vT);
-            ogdf::edge e_sk = skel_edge_at(*spqr, eT, vT);
-            auto ff = faces_of(CE, e_sk);
-            std::array<uint32_t, 2> ids = {fi_get_or_assign(vT, ff.first), fi_get_or_assign(vT, ff.second)};
-            FI.e_faces.emplace(key, ids);
-            return ids;
+    // A vertex's neighbours are the blocks hanging from it, then its home;
+    // a block's are its vertices.
+    std::size_t BlockForest::degree(const Node n) const
+    {
+        return n.is_block ? blocks[n.id].verts.size() : hanging[n.id].size() + 1;
+    }
+
+    // Node `k` next to `n`; its id is NONE for a vertex without a home.
+    BlockForest::Node BlockForest::neighbour(const Node n, const std::size_t k) const
+    {
+        if (n.is_block)
+        {
+            return Node{false, blocks[n.id].verts[k]};
+        }
+        return Node{true, k < hanging[n.id].size() ? hanging[n.id][k] : home[n.id]};
+    }
+
+    void BlockForest::visit(Walk &walk, const Node n, const std::uint64_t s) const
+    {
+        mark(n) = s;
+        if (!n.is_block)
+        {
+            walk.verts.push_back(n.id);
+        }
+        walk.stack.emplace_back(n, 0);
+    }
+
+    // Follows one more edge of the forest; false once the walk is done.
+    bool BlockForest::step(Walk &walk, const std::uint64_t s) const
+    {
+        if (walk.stack.empty())
+        {
+            return false;
+        }
+        auto &[n, k] = walk.stack.back();
+        if (k == degree(n))
+        {
+            walk.stack.pop_back();
+            return !walk.stack.empty();
+        }
+        const Node next = neighbour(n, k++);
+        if (next.id != NONE && mark(next) != s)
+        {
+            visit(walk, next, s);
+        }
+        return true;
+    }
+
+    std::size_t BlockForest::new_block(Change &change)
+    {
+        std::size_t b;
+        if (free_ids.empty())
+        {
+            b = blocks.size();
+            blocks.emplace_back();
+            bmark.push_back(0);
+        }
+        else
+        {
+            b = free_ids.back();
+            free_ids.pop_back();
+        }
+        change.changed.push_back(b);
+        return b;
+    }
+
+    void BlockForest::retire(const std::size_t b, Change &change)
+    {
+        blocks[b] = Block{};
+        free_ids.push_back(b);
+        change.retired.push_back(b);
+    }
+
+    void BlockForest::hang(const std::size_t b, const std::size_t p)
+    {
+        blocks[b].parent = p;
+        if (p != NONE)
+        {
+            blocks[b].slot = hanging[p].size();
+            hanging[p].push_back(b);
+        }
+    }
+
+    void BlockForest::unhang(const std::size_t b)
+    {
+        const std::size_t p = blocks[b].parent;
+        if (p != NONE)
+        {
+            std::vector<std::size_t> &list = hanging[p];
+            const std::size_t last = list.back();
+            list[blocks[b].slot] = last;
+            blocks[last].slot = blocks[b].slot;
+            list.pop_back();
+        }
+        blocks[b].parent = NONE;
+    }
+
+    // Turns the blocks between `v` and the root of its tree the other way
+    // up, leaving `v` on top without a home for the caller to give it.
+    void BlockForest::reroot(const std::size_t v)
+    {
+        std::vector<std::size_t> cuts{v}, path;
+        for (std::size_t c = v; home[c] != NONE;)
+        {
+            path.push_back(home[c]);
+            c = blocks[home[c]].parent;
+            if (c == NONE)
+            {
+                break;
+            }
+            cuts.push_back(c);
+        }
+        for (size_t k = 0; k < path.size(); ++k)
+        {
+            unhang(path[k]);
+            hang(path[k], cuts[k]);
+            home[cuts[k]] = k == 0 ? NONE : path[k - 1];
+        }
+    }
+
+    void BlockForest::insert(std::size_t u, std::size_t v, Change &change)
+    {
+        if (u > v)
+        {
+            std::swap(u, v);
+        }
+        if (comp[u] != comp[v])
+        {
+            link(u, v, change);
+            return;
+        }
+        const std::size_t b = common_block(u, v);
+        if (b != NONE)
+        {
+            blocks[b].edges.emplace_back(u, v);
+            change.changed.push_back(b);
+            return;
+        }
+        std::size_t top;
+        const std::vector<Node> seq = climb(u, v, top);
+        merge(seq, top, change);
+        blocks[change.changed.back()].edges.emplace_back(u, v);
+    }
+
+    // Joins two trees by a bridge block, hanging the smaller tree from it
+    // and relabeling that tree's vertices.
+    void BlockForest::link(const std::size_t u, const std::size_t v, Change &change)
+    {
+        const bool flip = comp_size[comp[u]] < comp_size[comp[v]];
+        const std::size_t big = flip ? v : u, small = flip ? u : v;
+        const std::size_t label = comp[big], old = comp[small];
+
+        reroot(small);
+        Walk walk;
+        const std::uint64_t s = ++stamp;
+        visit(walk, Node{false, small}, s);
+        while (step(walk, s))
+        {
+        }
+        for (std::size_t w : walk.verts)
+        {
+            comp[w] = label;
+        }
+        comp_size[label] += comp_size[old];
+        comp_size[old] = 0;
+
+        const std::size_t b = new_block(change);
+        blocks[b].edges.emplace_back(u, v);
+        blocks[b].verts = {big, small};
+        if (home[big] == NONE)
+        {
+            home[big] = b;
+        }
+        else
+        {
+            hang(b, big);
+        }
+        home[small] = b;
+    }
+
+    // Merges the blocks of the path `seq` into the largest of them, which
+    // takes the place of the highest and is reported last. The cut vertices
+    // inside the path lie in two of its blocks each and end up in the merged
+    // block only; the one at `top`, if the path turns at a vertex, keeps its
+    // home and has the merged block hang from it.
+    void BlockForest::merge(const std::vector<Node> &seq, const std::size_t top, Change &change)
+    {
+        std::vector<std::size_t> parts;
+        for (const Node &n : seq)
+        {
+            if (n.is_block)
+            {
+                parts.push_back(n.id);
+            }
+        }
+        const std::size_t parent = seq[top].is_block ? blocks[seq[top].id].parent : seq[top].id;
+        const std::size_t keep = *std::max_element(parts.begin(), parts.end(), [&](std::size_t a, std::size_t b)
+                                                   { return blocks[a].verts.size() + blocks[a].edges.size() <
+                                                            blocks[b].verts.size() + blocks[b].edges.size(); });
+        // cut vertices inside the path that `keep` holds already, and those
+        // to take over from the first of their two blocks only
+        std::unordered_set<std::size_t> held, once;
+        for (size_t k = 1; k + 1 < seq.size(); ++k)
+        {
+            if (!seq[k].is_block)
+            {
+                (seq[k - 1].id == keep || seq[k + 1].id == keep ? held : once).insert(seq[k].id);
+            }
+        }
+        for (std::size_t b : parts)
+        {
+            unhang(b);
+        }
+
+        Block &into = blocks[keep];
+        for (std::size_t b : parts)
+        {
+            if (b == keep)
+            {
+                continue;
+            }
+            for (std::size_t w : blocks[b].verts)
+            {
+                if (home[w] == b)
+                {
+                    home[w] = keep;
+                }
+                if (held.count(w))
+                {
+                    continue;
+                }
+                into.verts.push_back(w);
+                if (once.erase(w))
+                {
+                    held.insert(w);
+                }
+            }
+            into.edges.insert(into.edges.end(), blocks[b].edges.begin(), blocks[b].edges.end());
+            retire(b, change);
+        }
+        hang(keep, parent);
+        change.changed.push_back(keep);
+    }
+
+    void BlockForest::erase(const std::vector<VertexPair> &edges, Change &change)
+    {
+        std::map<std::size_t, std::vector<VertexPair>> lost;
+        for (auto [u, v] : edges)
+        {
+            lost[common_block(u, v)].emplace_back(std::min(u, v), std::max(u, v));
+        }
+        for (auto &[b, gone] : lost)
+        {
+            std::sort(gone.begin(), gone.end());
+            std::vector<VertexPair> &own = blocks[b].edges;
+            own.erase(std::remove_if(own.begin(), own.end(), [&](const VertexPair &e)
+                                     { return std::binary_search(gone.begin(), gone.end(), e); }),
+                      own.end());
+            recompute(b, change);
+        }
+    }
+
+    // Makes blocks of `pieces`, the blocks of what is left of a block with
+    // vertices `verts` that hung from `anchor`. The pieces holding `anchor`
+    // hang from it, and every other part becomes a tree of its own, rooted
+    // anywhere; a vertex left without edges there hands its home to a block
+    // hanging from it, or is isolated. Returns a vertex in each tree that
+    // came loose, after `anchor` itself unless that is NONE.
+    std::vector<std::size_t> BlockForest::place(std::vector<std::vector<VertexPair>> pieces,
+                                                const std::size_t anchor, const std::vector<std::size_t> &verts,
+                                                Change &change)
+    {
+        // the first piece each vertex lies in, in `local` for those with the
+        // stamp `s`, and the others it lies in
+        std::vector<std::size_t> ids;
+        std::unordered_map<std::size_t, std::vector<std::size_t>> more;
+        const std::uint64_t s = ++stamp;
+        for (size_t k = 0; k < pieces.size(); ++k)
+        {
+            const std::size_t b = new_block(change);
+            ids.push_back(b);
+            blocks[b].edges = std::move(pieces[k]);
+            for (auto [x, y] : blocks[b].edges)
+            {
+                for (std::size_t w : {x, y})
+                {
+                    if (vmark[w] != s)
+                    {
+                        vmark[w] = s;
+                        local[w] = k;
+                    }
+                    else if (local[w] == k || (more.count(w) && more[w].back() == k))
+                    {
+                        continue;
+                    }
+                    else
+                    {
+                        more[w].push_back(k);
+                    }
+                    blocks[b].verts.push_back(w);
+                }
+            }
+        }
+        auto each_piece = [&](std::size_t w, auto &&f)
+        {
+            f(local[w]);
+            auto it = more.find(w);
+            if (it != more.end())
+            {
+                for (std::size_t k : it->second)
+                {
+                    f(k);
+                }
+            }
+        };
+
+        std::vector<bool> placed(pieces.size(), false);
+        std::vector<std::size_t> queue;
+        std::size_t head = 0;
+        auto settle = [&](std::size_t k, std::size_t p)
+        {
+            placed[k] = true;
+            hang(ids[k], p);
+            queue.push_back(k);
+            for (; head < queue.size(); ++head)
+            {
+                const Block &b = blocks[ids[queue[head]]];
+                for (std::size_t w : b.verts)
+                {
+                    if (w == b.parent)
+                    {
+                        continue;
+                    }
+                    home[w] = ids[queue[head]];
+                    each_piece(w, [&](std::size_t next)
+                               {
+                                   if (!placed[next])
+                                   {
+                                       placed[next] = true;
+                                       hang(ids[next], w);
+                                       queue.push_back(next);
+                                   }
+                               });
+                }
+            }
+        };
+
+        std::vector<std::size_t> starts;
+        if (anchor != NONE)
+        {
+            starts.push_back(anchor);
+            if (vmark[anchor] == s)
+            {
+                each_piece(anchor, [&](std::size_t k)
+                           {
+                               if (!placed[k])
+                               {
+                                   settle(k, anchor);
+                               }
+                           });
+            }
+        }
+        for (std::size_t w : verts)
+        {
+            if (w == anchor)
+            {
+                continue;
+            }
+            if (vmark[w] != s)
+            {
+                starts.push_back(w);
+                home[w] = NONE;
+                if (!hanging[w].empty())
+                {
+                    const std::size_t r = hanging[w].back();
+                    unhang(r);
+                    home[w] = r;
+                }
+            }
+            else if (!placed[local[w]])
+            {
+                starts.push_back(w);
+                settle(local[w], NONE);
+            }
+        }
+        return starts;
+    }
+
+    // Block `b` lost edges: what is left of it takes its place.
+    void BlockForest::recompute(const std::size_t b, Change &change)
+    {
+        const std::size_t anchor = blocks[b].parent;
+        const std::vector<std::size_t> verts = std::move(blocks[b].verts);
+        std::vector<std::vector<VertexPair>> pieces = biconnected(blocks[b].edges);
+        unhang(b);
+        retire(b, change);
+        split(place(std::move(pieces), anchor, verts, change));
+    }
+
+    // `starts` lie in different trees, all carrying the label the first
+    // did before. Walks them in lockstep until one is left, and gives each
+    // of the others a label of its own, so relabeling costs no more than
+    // walking all but the largest a few times over.
+    void BlockForest::split(const std::vector<std::size_t> &starts)
+    {
+        if (starts.size() < 2)
+        {
+            return;
+        }
+        const std::size_t old = comp[starts[0]];
+        const std::uint64_t s = ++stamp;
+        std::vector<Walk> walks(starts.size());
+        std::vector<std::size_t> open;
+        for (size_t k = 0; k < starts.size(); ++k)
+        {
+            visit(walks[k], Node{false, starts[k]}, s);
+            open.push_back(k);
+        }
+        while (open.size() > 1)
+        {
+            for (size_t k = 0; k < open.size() && open.size() > 1;)
+            {
+                if (step(walks[open[k]], s))
+                {
+                    ++k;
+                }
+                else
+                {
+                    open.erase(open.begin() + static_cast<std::ptrdiff_t>(k));
+                }
+            }
+        }
+        for (size_t k = 0; k < walks.size(); ++k)
+        {
+            if (k == open.front())
+            {
+                continue;
+            }
+            const std::size_t label = comp_size.size();
+            comp_size.push_back(walks[k].verts.size());
+            comp_size[old] -= walks[k].verts.size();
+            for (std::size_t w : walks[k].verts)
+            {
+                comp[w] = label;
+            }
+        }
+    }
+
+    struct PlanarSubgraph::Impl
+    {
+        Impl(std::size_t num_verts,
+             const std::vector<Edge> &edges_all,
+             const std::vector<std::uint8_t> &edges_added_init);
+
+        // a fork shares the candidate list until either side changes it, and
+        // builds its own block forest the first time it needs it
+        std::unique_ptr<Impl> fork() const;
+
+        Impl(const Impl &) = delete;
+        Impl &operator=(const Impl &) = delete;
+        Impl(Impl &&) = delete;
+        Impl &operator=(Impl &&) = delete;
+        ~Impl();
+
+        std::size_t vertex_count() const;
+        bool is_vertex(std::size_t v) const;
+        std::size_t num_edges() const;
+        std::vector<Edge> edges() const;
+        Edge edge(std::size_t edge_id) const;
+        bool is_candidate(std::size_t edge_id) const;
+        bool is_selected(std::size_t edge_id) const;
+        bool in_transaction() const;
+        std::uint8_t state(std::size_t edge_id) const;
+        std::size_t num_required() const;
+        std::size_t evaluations() const;
+        std::uint8_t check_set(std::size_t edge_id, bool present);
+        std::uint8_t check_commit() const;
+        bool set_state(std::size_t edge_id, std::uint8_t state, std::vector<std::size_t> *flipped = nullptr);
+        bool is_planar() const;
+        std::size_t add_candidate(const Edge &e);
+        void remove_candidate(std::size_t edge_id, std::vector<std::size_t> *flipped = nullptr);
+        std::size_t add_vertex();
+        std::vector<std::size_t> remove_vertex(std::size_t v, std::vector<std::size_t> *flipped = nullptr);
+        std::uint8_t contract(std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                              std::vector<std::size_t> &candidate_map, std::vector<std::size_t> *flipped = nullptr);
+        // with `flipped`, also report the candidates whose addability the
+        // update changed
+        void set(std::size_t edge_id, bool present, std::vector<std::size_t> *flipped = nullptr);
+        std::vector<std::size_t> fill_greedy(const std::vector<std::size_t> &order,
+                                             std::vector<std::size_t> *flipped = nullptr);
+        std::vector<std::uint8_t> query();
+        std::shared_ptr<const std::vector<std::uint64_t>> snapshot(std::size_t &len);
+        bool is_addable(std::size_t edge_id);
+        std::vector<std::uint8_t> addable_among(const std::vector<std::size_t> &edge_ids);
+        bool obstruction(std::size_t edge_id, std::vector<std::size_t> &witness_ids);
+        std::vector<std::size_t> conflicts(std::size_t edge_id);
+        bool can_add_all(const std::vector<std::size_t> &edge_ids, std::vector<std::size_t> &conflict) const;
+        bool can_add(std::size_t u, std::size_t v);
+        bool embedding(std::vector<std::size_t> &rotation_offsets, std::vector<std::size_t> &rotation) const;
+        bool witness_embedding(std::size_t edge_id, std::vector<std::size_t> &rotation_offsets,
+                               std::vector<std::size_t> &rotation);
+        std::vector<std::size_t> addable_partners(std::size_t v);
+        bool can_attach(const std::vector<std::size_t> &verts);
+        bool attach_embedding(const std::vector<std::size_t> &verts, std::vector<std::size_t> &rotation_offsets,
+                              std::vector<std::size_t> &rotation) const;
+        void conflict_graph(std::vector<std::size_t> &offsets, std::vector<std::size_t> &neighbours);
+
+        bool begin();
+        bool commit(std::vector<std::size_t> *flipped = nullptr);
+        bool rollback();
+
+    private:
+        // `addable` entries never evaluated, or invalidated wholesale
+        static constexpr std::uint8_t UNKNOWN = 2;
+
+        Impl(std::size_t num_verts, std::shared_ptr<std::vector<Edge>> candidates);
+
+        const std::vector<Edge> &edges_all() const { return *candidates; }
+        // the selection `forest` reflects: during a transaction, the one at
+        // `begin`
+        const std::vector<std::uint8_t> &applied() const { return txn_saved ? *txn_saved : edges_added; }
+        static std::pair<std::size_t, std::size_t> pair_key(const Edge &e) { return std::minmax(e.u, e.v); }
+        bool link(std::size_t i);
+        bool unlink(std::size_t i);
+        void ensure_graph();
+        void apply(const std::vector<std::size_t> &changed);
+        void refresh();
+        bool resolve(std::size_t i);
+        std::vector<std::size_t> speculate_conflicts(std::size_t i);
+        bool pair_addable(std::size_t u, std::size_t v);
+        bool embed_applied(std::optional<std::size_t> extra, const std::vector<std::size_t> *hub,
+                           std::vector<std::size_t> &rotation_offsets, std::vector<std::size_t> &rotation) const;
+        std::vector<std::size_t> attach_targets(const std::vector<std::size_t> &verts) const;
+        std::vector<std::pair<std::size_t, bool>> watch(const std::vector<std::size_t> &changed,
+                                                        const std::vector<std::size_t> &also = {});
+        std::vector<std::size_t> flipped_since(const std::vector<std::pair<std::size_t, bool>> &watched);
+        bool batch_planar(const std::vector<std::size_t> &batch);
+        bool can_add_along_bc(std::size_t u, std::size_t v);
+        BlockInfo &block_info(std::size_t b);
+        bool path_settled(std::size_t u, std::size_t v, std::uint64_t stamp) const;
+
+        // vertex ids are never reused either; a removed vertex stays in
+        // `forest`, isolated
+        std::size_t num_verts;
+        std::vector<std::uint8_t> removed_verts;
+        // ids are never reused; removed candidates stay behind as tombstones.
+        // Shared between forks, and copied before a shared list is grown.
+        std::shared_ptr<std::vector<Edge>> candidates;
+        std::vector<std::uint8_t> removed;
+        // `FREE`, `REQUIRED` or `FORBIDDEN` per candidate
+        std::vector<std::uint8_t> states;
+        // live candidates in state REQUIRED
+        std::size_t required = 0;
+
+        std::vector<std::uint8_t> edges_added;
+        // last evaluated answer per candidate, and the epoch it was last
+        // known to hold in; the cheap cases are never stored here
+        std::vector<std::uint8_t> addable;
+        std::vector<std::uint64_t> checked_at;
+        // set on a fork whose `addable` still holds for its selection, until
+        // the fork changes it before building `forest`
+        bool inherited = false;
+        std::size_t evaluated = 0;
+        // `addable` packed into words once fully resolved; reset by updates
+        mutable std::shared_ptr<const std::vector<std::uint64_t>> addable_snap;
+        mutable std::mutex mu;
+
+        // selection at `begin`, and the candidates `set` since
+        std::optional<std::vector<std::uint8_t>> txn_saved;
+        std::vector<std::size_t> txn_touched;
+
+        // `forest`, `linked` and `pair_slots` are only set up by
+        // `ensure_graph`
+        bool materialized = false;
+        // the applied selection as a simple graph: selected parallel
+        // candidates share one edge, and selected loops have none. `linked`
+        // says which candidates are represented, and `pair_slots` how many
+        // of them join each pair of vertices.
+        BlockForest forest;
+        std::vector<std::uint8_t> linked;
+        std::map<std::pair<std::size_t, std::size_t>, std::size_t> pair_slots;
+
+        // pairs whose edge came or went since the last `refresh`, each with
+        // whether `forest` has it
+        std::map<std::pair<std::size_t, std::size_t>, bool> pending;
+        bool stale = false;
+        // advanced by every `refresh` that finds the selection changed
+        std::uint64_t epoch = 0;
+
+        // per block id of `forest`: the SPQR-based cache, built on first use,
+        // and the epoch in which the block took on its edge set
+        std::vector<std::unique_ptr<BlockInfo>> block_infos;
+        std::vector<std::uint64_t> block_since;
+    };
+
+    // Kuratowski subgraph among the candidates `ids`, as sorted candidate
+    // ids; empty if they form a planar graph. Parallel candidates are
+    // represented by the first of them.
+    std::vector<std::size_t> kuratowski_among(const std::vector<Edge> &edges_all, const std::vector<std::size_t> &ids)
+    {
+        ogdf::Graph H;
+        std::unordered_map<std::size_t, ogdf::node> nodes;
+        auto node_of = [&](std::size_t x)
+        {
+            auto [it, fresh] = nodes.try_emplace(x, nullptr);
+            if (fresh)
+            {
+                it->second = H.newNode();
+            }
+            return it->second;
+        };
+
+        ogdf::EdgeArray<std::size_t> id_of(H, 0);
+        std::set<std::pair<std::size_t, std::size_t>> seen;
+        for (size_t i : ids)
+        {
+            auto [u, v] = edges_all[i];
+            if (u == v || !seen.emplace(std::min(u, v), std::max(u, v)).second)
+            {
+                continue;
+            }
+            id_of[H.newEdge(node_of(u), node_of(v))] = i;
+        }
+
+        ogdf::BoyerMyrvold bm;
+        ogdf::SList<ogdf::KuratowskiWrapper> witnesses;
+        if (bm.planarEmbedDestructive(H, witnesses, 1, false, false, false, true) || witnesses.empty())
+        {
+            return {};
+        }
+        std::vector<std::size_t> out;
+        for (ogdf::edge e : witnesses.front().edgeList)
+        {
+            out.push_back(id_of[e]);
+        }
+        std::sort(out.begin(), out.end());
+        out.erase(std::unique(out.begin(), out.end()), out.end());
+        return out;
+    }
+
+    // Whether the graph of the vertex pairs `pairs`, each taken once as
+    // `(min, max)`, is planar.
+    bool planar_pairs(std::vector<std::pair<std::size_t, std::size_t>> pairs)
+    {
+        ogdf::Graph H;
+        std::unordered_map<std::size_t, ogdf::node> nodes;
+        auto node_of = [&](std::size_t x)
+        {
+            auto [it, fresh] = nodes.try_emplace(x, nullptr);
+            if (fresh)
+            {
+                it->second = H.newNode();
+            }
+            return it->second;
+        };
+        std::sort(pairs.begin(), pairs.end());
+        pairs.erase(std::unique(pairs.begin(), pairs.end()), pairs.end());
+        for (auto [u, v] : pairs)
+        {
+            H.newEdge(node_of(u), node_of(v));
+        }
+        ogdf::BoyerMyrvold bm;
+        return bm.isPlanarDestructive(H);
+    }
+
+    inline std::pair<ogdf::face, ogdf::face> faces_of(const ogdf::ConstCombinatorialEmbedding &CE, ogdf::adjEntry a)
+    {
+        return {CE.leftFace(a), CE.rightFace(a)};
+    }
+
+    inline std::pair<ogdf::face, ogdf::face> faces_of(const ogdf::ConstCombinatorialEmbedding &CE, ogdf::edge e)
+    {
+        return faces_of(CE, e->adjSource());
+    }
+
+    inline bool contains_face_id(const std::vector<uint32_t> &v, uint32_t id)
+    {
+        return std::binary_search(v.begin(), v.end(), id);
+    }
+
+    inline ogdf::edge skel_edge_at(const ogdf::StaticPlanarSPQRTree &spqr, ogdf::edge e_t, ogdf::node v_t)
+    {
+        return (e_t->source() == v_t) ? spqr.skeletonEdgeSrc(e_t) : spqr.skeletonEdgeTgt(e_t);
+    }
+
+    struct BlockInfo
+    {
+        ogdf::Graph block;
+        // vertex id to node of `block`
+        std::unordered_map<std::size_t, ogdf::node> g_to_b;
+
+        std::unique_ptr<ogdf::StaticPlanarSPQRTree> spqr;
+        const ogdf::Graph *tree = nullptr;
+
+        ogdf::NodeArray<ogdf::node> parent_t;
+        ogdf::NodeArray<ogdf::edge> parent_edge_t;
+        ogdf::NodeArray<int> depth_t;
+        std::vector<ogdf::NodeArray<ogdf::node>> up;
+        ogdf::NodeArray<int> tin, tout;
+
+        ogdf::NodeArray<ogdf::node> repr_t;
+
+        ogdf::NodeArray<std::optional<ogdf::ConstCombinatorialEmbedding>> emb_r;
+
+        struct FaceIndex
+        {
+            std::unordered_map<size_t, uint32_t> face_id;
+            std::unordered_map<size_t, std::vector<uint32_t>> v_faces;
+            std::unordered_map<size_t, std::array<uint32_t, 2>> e_faces;
+            uint32_t next_id = 0;
+        };
+        ogdf::NodeArray<FaceIndex *> r_face_index;
+        std::vector<std::unique_ptr<FaceIndex>> r_face_owned;
+
+        struct PairKey
+        {
+            ogdf::node u{nullptr}, v{nullptr};
+        };
+        struct PairKeyHash
+        {
+            size_t operator()(const PairKey &k) const noexcept
+            {
+                size_t a = (size_t)(uintptr_t)k.u;
+                size_t b = (size_t)(uintptr_t)k.v;
+                if (a > b)
+                {
+                    std::swap(a, b);
+                }
+                return a * 1315423911u ^ (b + 0x9e3779b97f4a7c15ull + (a << 6) + (a >> 2));
+            }
+        };
+        struct PairKeyEq
+        {
+            bool operator()(const PairKey &x, const PairKey &y) const noexcept
+            {
+                size_t ax = (size_t)(uintptr_t)x.u, bx = (size_t)(uintptr_t)x.v;
+                size_t ay = (size_t)(uintptr_t)y.u, by = (size_t)(uintptr_t)y.v;
+                if (ax > bx)
+                {
+                    std::swap(ax, bx);
+                }
+                if (ay > by)
+                {
+                    std::swap(ay, by);
+                }
+                return ax == ay && bx == by;
+            }
+        };
+        mutable std::unordered_map<PairKey, bool, PairKeyHash, PairKeyEq> memo;
+
+        // Copies the block with edges `edges`, given as pairs of vertex ids,
+        // or the union of several that an edge would merge.
+        explicit BlockInfo(const std::vector<std::pair<std::size_t, std::size_t>> &edges)
+        {
+            auto to_b = [&](std::size_t x)
+            {
+                auto [it, fresh] = g_to_b.try_emplace(x, nullptr);
+                if (fresh)
+                {
+                    it->second = block.newNode();
+                }
+                return it->second;
+            };
+            for (auto [x, y] : edges)
+            {
+                ogdf::node xB = to_b(x);
+                ogdf::node yB = to_b(y);
+                block.newEdge(xB, yB);
+            }
+
+            const int nv = block.numberOfNodes();
+            const int ne = block.numberOfEdges();
+            if (nv < 2 || (nv == 2 && ne < 3))
+            {
+                static ogdf::Graph k_empty_graph;
+                spqr = nullptr;
+                tree = &k_empty_graph;
+                repr_t.init(block, nullptr);
+                emb_r.init(k_empty_graph);
+                return;
+            }
+
+            spqr = std::make_unique<ogdf::StaticPlanarSPQRTree>(block, false);
+            tree = &spqr->tree();
+
+            repr_t.init(block, nullptr);
+            for (ogdf::node vT = tree->firstNode(); vT; vT = vT->succ())
+            {
+                const ogdf::Skeleton &Sk = spqr->skeleton(vT);
+                for (ogdf::node xM = Sk.getGraph().firstNode(); xM; xM = xM->succ())
+                {
+                    ogdf::node orig = Sk.original(xM);
+                    if (!repr_t[orig])
+                    {
+                        repr_t[orig] = vT;
+                    }
+                }
+            }
+            emb_r.init(*tree);
+            r_face_index.init(*tree, nullptr);
+            build_lca();
+        }
+
+        ogdf::ConstCombinatorialEmbedding &embedding_r(ogdf::node vT)
+        {
+            auto &slot = emb_r[vT];
+            if (!slot)
+            {
+                const ogdf::Skeleton &S = spqr->skeleton(vT);
+                slot = ogdf::ConstCombinatorialEmbedding(S.getGraph());
+            }
+            return *slot;
+        }
+
+        uint32_t fi_get_or_assign(ogdf::node vT, ogdf::face f)
+        {
+            auto &FI = *r_face_index[vT];
+            size_t key = (size_t)(uintptr_t)f;
+            auto it = FI.face_id.find(key);
+            if (it != FI.face_id.end())
+            {
+                return it->second;
+            }
+            uint32_t id = FI.next_id++;
+            FI.face_id.emplace(key, id);
+            return id;
+        }
+
+        void ensure_face_index(ogdf::node vT)
+        {
+            if (spqr->typeOf(vT) != ogdf::SPQRTree::NodeType::RNode)
+            {
+                return;
+            }
+            if (r_face_index[vT])
+            {
+                return;
+            }
+            r_face_owned.emplace_back(std::make_unique<BlockInfo::FaceIndex>());
+            r_face_index[vT] = r_face_owned.back().get();
+        }
+
+        const std::vector<uint32_t> &vertex_face_ids(ogdf::node vT, ogdf::node bB)
+        {
+            ensure_face_index(vT);
+            auto &FI = *r_face_index[vT];
+            size_t key = (size_t)(uintptr_t)bB;
+            auto it = FI.v_faces.find(key);
+            if (it != FI.v_faces.end())
+            {
+                return it->second;
+            }
+
+            const ogdf::Skeleton &S = spqr->skeleton(vT);
+            auto &CE = embedding_r(vT);
+            std::vector<uint32_t> vec;
+            for (ogdf::node xM = S.getGraph().firstNode(); xM; xM = xM->succ())
+            {
+                if (S.original(xM) != bB)
+                {
+                    continue;
+                }
+                for (ogdf::adjEntry a = xM->firstAdj(); a; a = a->succ())
+                {
+                    auto ff = faces_of(CE, a);
+                    vec.push_back(fi_get_or_assign(vT, ff.first));
+                    vec.push_back(fi_get_or_assign(vT, ff.second));
+                }
+            }
+            std::sort(vec.begin(), vec.end());
+            vec.erase(std::unique(vec.begin(), vec.end()), vec.end());
+            auto [pos, _] = FI.v_faces.emplace(key, std::move(vec));
+            return pos->second;
+        }
+
+        std::array<uint32_t, 2> edge_face_ids(ogdf::node vT, ogdf::edge eT)
+        {
+            ensure_face_index(vT);
+            auto &FI = *r_face_index[vT];
+            size_t key = (size_t)(uintptr_t)eT;
+            auto it = FI.e_faces.find(key);
+            if (it != FI.e_faces.end())
+            {
+                return it->second;
+            }
+
+            auto &CE = embedding_r(vT);
+            ogdf::edge e_sk = skel_edge_at(*spqr, eT, vT);
+            auto ff = faces_of(CE, e_sk);
+            std::array<uint32_t, 2> ids = {fi_get_or_assign(vT, ff.first), fi_get_or_assign(vT, ff.second)};
+            FI.e_faces.emplace(key, ids);
+            return ids;
         }
 
         Mask endpoint_mask_at(ogdf::node v_t, ogdf::edge up_edge, ogdf::node uB);
@@ -312,728 +1313,2114 @@ namespace graph
         bool is_on_path_up_to_lca(ogdf::node x, ogdf::node start, ogdf::node lca) const;
         ogdf::node lca(ogdf::node a, ogdf::node b) const;
         bool cofacial_at_node(ogdf::node vT, ogdf::node aB, ogdf::node bB);
-        bool block_linkable(ogdf::node hA, ogdf::node hB);
+        bool block_linkable(std::size_t a, std::size_t b);
+
+        BlockInfo(const BlockInfo &) = delete;
+        BlockInfo &operator=(const BlockInfo &) = delete;
+        BlockInfo(BlockInfo &&) = default;
+        BlockInfo &operator=(BlockInfo &&) = default;
+
+    private:
+        void build_lca();
+    };
+
+    bool BlockInfo::edge_contains_b(ogdf::node parent, ogdf::node child, ogdf::node bB) const
+    {
+        ogdf::edge e_up = parent_edge_t[child];
+        if (!e_up)
+        {
+            return false;
+        }
+        const ogdf::Skeleton &sk = spqr->skeleton(parent);
+        ogdf::edge e_sk = skel_edge_at(*spqr, e_up, parent);
+        return sk.original(e_sk->source()) == bB || sk.original(e_sk->target()) == bB;
+    }
+
+    ogdf::node BlockInfo::kth_ancestor(ogdf::node v, int k) const
+    {
+        if (!v || k < 0)
+        {
+            return nullptr;
+        }
+        for (int i = 0; v && k; ++i)
+        {
+            if (k & 1)
+            {
+                v = up[i][v];
+            }
+            k >>= 1;
+        }
+        return v;
+    }
+
+    bool BlockInfo::is_ancestor(ogdf::node a, ogdf::node b) const
+    {
+        return tin[a] <= tin[b] && tout[b] <= tout[a];
+    }
+
+    bool BlockInfo::is_on_path_up_to_lca(ogdf::node x, ogdf::node start, ogdf::node lca) const
+    {
+        return is_ancestor(x, start) && is_ancestor(lca, x);
+    }
+
+    void BlockInfo::build_lca()
+    {
+        const ogdf::Graph &T = *tree;
+        parent_t.init(T, nullptr);
+        parent_edge_t.init(T, nullptr);
+        depth_t.init(T, 0);
+
+        ogdf::node root = spqr->rootNode();
+
+        std::vector<ogdf::node> st{root};
+        st.reserve(T.numberOfNodes());
+        parent_t[root] = nullptr;
+        parent_edge_t[root] = nullptr;
+
+        while (!st.empty())
+        {
+            ogdf::node v = st.back();
+            st.pop_back();
+            for (ogdf::adjEntry a = v->firstAdj(); a; a = a->succ())
+            {
+                ogdf::edge e = a->theEdge();
+                ogdf::node w = a->twinNode();
+                if (w == parent_t[v])
+                {
+                    continue;
+                }
+                parent_t[w] = v;
+                parent_edge_t[w] = e;
+                depth_t[w] = depth_t[v] + 1;
+                st.push_back(w);
+            }
+        }
+
+        const int n = T.numberOfNodes();
+        int LOG = 1;
+        while ((1 << LOG) <= n)
+        {
+            ++LOG;
+        }
+        up.clear();
+        up.reserve(LOG);
+        up.emplace_back(T, nullptr);
+        for (ogdf::node v = T.firstNode(); v; v = v->succ())
+        {
+            up[0][v] = parent_t[v];
+        }
+        for (int k = 1; k < LOG; ++k)
+        {
+            up.emplace_back(T, nullptr);
+            for (ogdf::node v = T.firstNode(); v; v = v->succ())
+            {
+                ogdf::node mid = up[k - 1][v];
+                up[k][v] = mid ? up[k - 1][mid] : nullptr;
+            }
+        }
+
+        tin.init(T, 0);
+        tout.init(T, 0);
+        int timer = 0;
+        std::vector<std::pair<ogdf::node, ogdf::adjEntry>> dfsSt;
+        dfsSt.reserve(T.numberOfNodes());
+        tin[root] = ++timer;
+        dfsSt.emplace_back(root, root->firstAdj());
+        while (!dfsSt.empty())
+        {
+            auto &fr = dfsSt.back();
+            ogdf::node v = fr.first;
+            ogdf::adjEntry &it = fr.second;
+            while (it && parent_t[it->twinNode()] != v)
+            {
+                it = it->succ();
+            }
+            if (it)
+            {
+                ogdf::node w = it->twinNode();
+                it = it->succ();
+                tin[w] = ++timer;
+                dfsSt.emplace_back(w, w->firstAdj());
+            }
+            else
+            {
+                tout[v] = timer;
+                dfsSt.pop_back();
+            }
+        }
+    }
+
+    ogdf::node BlockInfo::lca(ogdf::node a, ogdf::node b) const
+    {
+        if (a == b)
+        {
+            return a;
+        }
+        int da = depth_t[a], db = depth_t[b];
+        if (da < db)
+        {
+            std::swap(a, b), std::swap(da, db);
+        }
+        int diff = da - db;
+        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        {
+            if ((diff >> k) & 1)
+            {
+                a = up[k][a];
+            }
+        }
+        if (a == b)
+        {
+            return a;
+        }
+        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        {
+            if (up[k][a] != up[k][b])
+            {
+                a = up[k][a];
+                b = up[k][b];
+            }
+        }
+        return parent_t[a];
+    }
+
+    Mask BlockInfo::endpoint_mask_at(ogdf::node v_t, ogdf::edge up_edge, ogdf::node uB)
+    {
+        if (!up_edge)
+        {
+            return 0;
+        }
+        if (spqr->typeOf(v_t) != ogdf::SPQRTree::NodeType::RNode)
+        {
+            return 3;
+        }
+        auto ef = edge_face_ids(v_t, up_edge);
+        const auto &vf = vertex_face_ids(v_t, uB);
+        Mask m = 0;
+        if (contains_face_id(vf, ef[0]))
+        {
+            m |= 1;
+        }
+        if (contains_face_id(vf, ef[1]))
+        {
+            m |= 2;
+        }
+        return m;
+    }
+
+    std::pair<ogdf::edge, Mask> BlockInfo::walk_side_to_lca(ogdf::node start_t, ogdf::node lca_t,
+                                                            ogdf::node endpoint_b)
+    {
+        if (start_t == lca_t)
+        {
+            return {nullptr, 0};
+        }
+
+        ogdf::edge e_up = parent_edge_t[start_t];
+        ogdf::node parent_at_boundary = parent_t[start_t];
+
+        Mask seed_mask = endpoint_mask_at(parent_at_boundary, e_up, endpoint_b);
+        if (seed_mask == 0 && spqr->typeOf(parent_at_boundary) == ogdf::SPQRTree::NodeType::RNode)
+        {
+            seed_mask = 3;
+        }
+        if (!seed_mask)
+        {
+            return {e_up, 0};
+        }
+
+        int steps_to_lca = depth_t[parent_at_boundary] - depth_t[lca_t];
+        if (steps_to_lca == 0)
+        {
+            return {e_up, seed_mask};
+        }
+
+        Mask mask = seed_mask;
+        ogdf::node child = start_t;
+        for (int s = 0; s < steps_to_lca; ++s)
+        {
+            ogdf::node cur = parent_t[child];
+            if (cur == lca_t)
+            {
+                break;
+            }
+
+            if (spqr->typeOf(cur) == ogdf::SPQRTree::NodeType::RNode && mask)
+            {
+                mask = 3;
+            }
+
+            if (spqr->typeOf(cur) == ogdf::SPQRTree::NodeType::RNode)
+            {
+                auto fin = edge_face_ids(cur, parent_edge_t[child]);
+                auto fout = edge_face_ids(cur, parent_edge_t[cur]);
+                Mask next_mask = 0;
+                if (mask & 1)
+                {
+                    if (fin[0] == fout[0])
+                    {
+                        next_mask |= 1;
+                    }
+                    if (fin[0] == fout[1])
+                    {
+                        next_mask |= 2;
+                    }
+                }
+                if (mask & 2)
+                {
+                    if (fin[1] == fout[0])
+                    {
+                        next_mask |= 1;
+                    }
+                    if (fin[1] == fout[1])
+                    {
+                        next_mask |= 2;
+                    }
+                }
+                mask = next_mask;
+            }
+            if (!mask)
+            {
+                break;
+            }
+            child = cur;
+        }
+        if (!mask)
+        {
+            return {e_up, 0};
+        }
+
+        ogdf::node child_below_lca = kth_ancestor(parent_at_boundary, steps_to_lca - 1);
+        ogdf::edge incoming_at_lca = child_below_lca ? parent_edge_t[child_below_lca] : e_up;
+        return {incoming_at_lca, mask};
+    }
+
+    ogdf::node BlockInfo::walk_up_containing(ogdf::node start, ogdf::node stop, ogdf::node bB) const
+    {
+        ogdf::node repr = repr_t[bB];
+        ogdf::node cur = start;
+        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        {
+            ogdf::node cand = up[k][cur];
+            if (!cand || depth_t[cand] < depth_t[stop])
+            {
+                continue;
+            }
+            if (repr == cand)
+            {
+                cur = cand;
+            }
+            else if (is_ancestor(cand, repr))
+            {
+                const int dist = depth_t[repr] - depth_t[cand];
+                ogdf::node child = kth_ancestor(repr, dist - 1);
+                if (child && edge_contains_b(cand, child, bB))
+                {
+                    cur = cand;
+                }
+            }
+        }
+        return cur;
+    }
+
+    ogdf::node BlockInfo::walk_down_containing(ogdf::node from, ogdf::node toward, ogdf::node bB) const
+    {
+        if (!from || !toward)
+        {
+            return from;
+        }
+        int dist = depth_t[toward] - depth_t[from];
+        if (dist <= 0)
+        {
+            return from;
+        }
+
+        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        {
+            int step = 1 << k;
+            if (step > dist)
+            {
+                continue;
+            }
+
+            ogdf::node child = kth_ancestor(toward, dist - step);
+            ogdf::node parent = parent_t[child];
+            if (edge_contains_b(parent, child, bB))
+            {
+                dist -= step;
+            }
+        }
+        return kth_ancestor(toward, dist);
+    }
+
+    std::pair<ogdf::node, ogdf::node> BlockInfo::compute_boundaries(ogdf::node aT, ogdf::node bT, ogdf::node w,
+                                                                    ogdf::node aB, ogdf::node bB) const
+    {
+        ogdf::node aBoundary = walk_up_containing(aT, w, aB);
+        if (aBoundary == w)
+        {
+            aBoundary = walk_down_containing(w, bT, aB);
+        }
+
+        ogdf::node bBoundary = walk_up_containing(bT, w, bB);
+        if (bBoundary == w)
+        {
+            bBoundary = walk_down_containing(w, aT, bB);
+        }
+
+        return {aBoundary, bBoundary};
+    }
+
+    inline bool share_faces(std::pair<ogdf::face, ogdf::face> A, std::pair<ogdf::face, ogdf::face> B)
+    {
+        return A.first == B.first || A.first == B.second || A.second == B.first || A.second == B.second;
+    }
+
+    bool BlockInfo::cofacial_at_node(ogdf::node vT, ogdf::node aB, ogdf::node bB)
+    {
+        if (spqr->typeOf(vT) != ogdf::SPQRTree::NodeType::RNode)
+        {
+            return true;
+        }
+        const auto &A = vertex_face_ids(vT, aB);
+        const auto &B = vertex_face_ids(vT, bB);
+        size_t i = 0, j = 0;
+        while (i < A.size() && j < B.size())
+        {
+            if (A[i] < B[j])
+            {
+                ++i;
+            }
+            else if (A[i] > B[j])
+            {
+                ++j;
+            }
+            else
+            {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    bool BlockInfo::block_linkable(const std::size_t a, const std::size_t b)
+    {
+        if (!spqr)
+        {
+            return true;
+        }
+
+        auto ia = g_to_b.find(a);
+        auto ib = g_to_b.find(b);
+        if (ia == g_to_b.end() || ib == g_to_b.end())
+        {
+            return false;
+        }
+        ogdf::node aB = ia->second;
+        ogdf::node bB = ib->second;
+        if (aB == bB)
+        {
+            return true;
+        }
+
+        ogdf::node aT = repr_t[aB];
+        ogdf::node bT = repr_t[bB];
+        if (!aT || !bT)
+        {
+            return false;
+        }
+
+        BlockInfo::PairKey key{aB, bB};
+        if (auto it = memo.find(key); it != memo.end())
+        {
+            return it->second;
+        }
+        auto memo_return = [&](bool v)
+        {
+            memo.emplace(key, v);
+            return v;
+        };
+
+        if (aT == bT)
+        {
+            return memo_return(cofacial_at_node(aT, aB, bB));
+        }
+
+        ogdf::node w = lca(aT, bT);
+
+        auto [a_boundary, b_boundary] = compute_boundaries(aT, bT, w, aB, bB);
+        if (a_boundary != b_boundary)
+        {
+            auto crossed = [&](ogdf::node path_start, ogdf::node first_b, ogdf::node second_b)
+            {
+                if (!is_on_path_up_to_lca(second_b, path_start, w))
+                {
+                    return false;
+                }
+                bool first_on = is_on_path_up_to_lca(first_b, path_start, w);
+                return !first_on || (depth_t[second_b] > depth_t[first_b]);
+            };
+            if (crossed(aT, a_boundary, b_boundary) || crossed(bT, b_boundary, a_boundary))
+            {
+                return memo_return(true);
+            }
+        }
+
+        ogdf::node start_left = a_boundary;
+        ogdf::node start_right = b_boundary;
+        w = lca(start_left, start_right);
+
+        auto [left_into_w, left_mask] = walk_side_to_lca(start_left, w, aB);
+        auto [right_into_w, right_mask] = walk_side_to_lca(start_right, w, bB);
+
+        bool left_seed_ok = endpoint_mask_at(start_left, parent_edge_t[start_left], aB) != 0;
+        bool right_seed_ok = endpoint_mask_at(start_right, parent_edge_t[start_right], bB) != 0;
+
+        bool left_ok = (start_left == w) || (left_seed_ok && left_mask != 0);
+        bool right_ok = (start_right == w) || (right_seed_ok && right_mask != 0);
+        if (!left_ok || !right_ok)
+        {
+            return memo_return(false);
+        }
+
+        if (spqr->typeOf(w) != ogdf::SPQRTree::NodeType::RNode)
+        {
+            return memo_return(true);
+        }
+
+        if (left_into_w && right_into_w)
+        {
+            auto &ce_w = embedding_r(w);
+            ogdf::edge e_sk_l = skel_edge_at(*spqr, left_into_w, w);
+            ogdf::edge e_sk_r = skel_edge_at(*spqr, right_into_w, w);
+            return memo_return(share_faces(faces_of(ce_w, e_sk_l), faces_of(ce_w, e_sk_r)));
+        }
+
+        if (left_into_w || right_into_w)
+        {
+            ogdf::edge in_e = left_into_w ? left_into_w : right_into_w;
+            ogdf::node ep = left_into_w ? bB : aB;
+            return memo_return(endpoint_mask_at(w, in_e, ep) != 0);
+        }
+
+        return memo_return(cofacial_at_node(w, aB, bB));
+    }
+
+    // Whether every block on the path between the two vertices has kept its
+    // edge set since epoch `stamp`. An answer depends only on those blocks,
+    // and a path of blocks that all existed back then was the path back then
+    // too, so such an answer still holds.
+    bool PlanarSubgraph::Impl::path_settled(const std::size_t u, const std::size_t v, const std::uint64_t stamp) const
+    {
+        for (const BlockForest::Step &step : forest.path(u, v))
+        {
+            if (block_since[step.block] > stamp)
+            {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    BlockInfo &PlanarSubgraph::Impl::block_info(const std::size_t b)
+    {
+        std::unique_ptr<BlockInfo> &info = block_infos[b];
+        if (!info)
+        {
+            info = std::make_unique<BlockInfo>(forest.block(b).edges);
+        }
+        return *info;
+    }
 
-        BlockInfo(const BlockInfo &) = delete;
-        BlockInfo &operator=(const BlockInfo &) = delete;
-        BlockInfo(BlockInfo &&) = default;
-        BlockInfo &operator=(BlockInfo &&) = default;
+    // An edge can join iff each block on its path can take an edge between
+    // where the path enters and leaves it.
+    bool PlanarSubgraph::Impl::can_add_along_bc(const std::size_t u, const std::size_t v)
+    {
+        for (const BlockForest::Step &step : forest.path(u, v))
+        {
+            if (!block_info(step.block).block_linkable(step.x, step.y))
+            {
+                return false;
+            }
+        }
+        return true;
+    }
 
-    private:
-        void build_lca();
-    };
+    PlanarSubgraph::Impl::Impl(const std::size_t num_verts, std::shared_ptr<std::vector<Edge>> candidates_in)
+        : num_verts(num_verts),
+          removed_verts(num_verts, 0u),
+          candidates(std::move(candidates_in)),
+          removed(candidates->size(), 0u),
+          states(candidates->size(), FREE),
+          edges_added(candidates->size(), 0u),
+          addable(candidates->size(), UNKNOWN),
+          checked_at(candidates->size(), 0)
+    {
+    }
 
-    bool BlockInfo::edge_contains_b(ogdf::node parent, ogdf::node child, ogdf::node bB) const
+    PlanarSubgraph::Impl::Impl(const std::size_t num_verts,
+                               const std::vector<Edge> &edges_all_in,
+                               const std::vector<std::uint8_t> &edges_added_init)
+        : Impl(num_verts, std::make_shared<std::vector<Edge>>(edges_all_in))
     {
-        ogdf::edge e_up = parent_edge_t[child];
-        if (!e_up)
+
+        if (edges_added_init.size() != edges_all_in.size())
+        {
+            throw std::invalid_argument("edges_added must match edges_all length");
+        }
+
+        for (const auto &[u, v] : edges_all())
+        {
+            if (u >= num_verts || v >= num_verts)
+            {
+                throw std::out_of_range("edge endpoint index out of range");
+            }
+        }
+
+        for (size_t i = 0; i < edges_added_init.size(); ++i)
+        {
+            edges_added[i] = edges_added_init[i] ? 1u : 0u;
+        }
+
+        stale = true;
+    }
+
+    PlanarSubgraph::Impl::~Impl() = default;
+
+    std::unique_ptr<PlanarSubgraph::Impl> PlanarSubgraph::Impl::fork() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot fork during a transaction");
+        }
+        std::unique_ptr<Impl> out(new Impl(num_verts, candidates));
+        out->removed = removed;
+        out->states = states;
+        out->required = required;
+        out->removed_verts = removed_verts;
+        out->edges_added = edges_added;
+        out->addable_snap = addable_snap;
+        // the fork starts over at epoch 0 without a forest, so it keeps
+        // only answers known to hold for the selection as it stands
+        for (size_t i = 0; i < addable.size(); ++i)
+        {
+            const bool current = materialized ? !stale && checked_at[i] == epoch : inherited;
+            if (current && addable[i] != UNKNOWN)
+            {
+                out->addable[i] = addable[i];
+                out->inherited = true;
+            }
+        }
+        return out;
+    }
+
+    // Builds `forest` from the applied selection. No block counts as
+    // changed, so answers a fork inherited stay valid.
+    void PlanarSubgraph::Impl::ensure_graph()
+    {
+        if (materialized)
+        {
+            return;
+        }
+        materialized = true;
+        linked.assign(edges_all().size(), 0u);
+        std::vector<std::pair<std::size_t, std::size_t>> simple;
+        for (size_t i = 0; i < linked.size(); ++i)
+        {
+            if (applied()[i] && link(i))
+            {
+                simple.push_back(pair_key(edges_all()[i]));
+            }
+        }
+        forest.build(num_verts, simple);
+        block_infos.resize(forest.block_ids());
+        block_since.assign(forest.block_ids(), 0);
+        stale = true;
+    }
+
+    // Represents selected candidate `i`, sharing the edge of any selected
+    // parallel candidate. Returns whether the simple graph gained an edge.
+    bool PlanarSubgraph::Impl::link(const std::size_t i)
+    {
+        const Edge &e = edges_all()[i];
+        if (e.u == e.v || linked[i])
         {
             return false;
         }
-        const ogdf::Skeleton &sk = spqr->skeleton(parent);
-        ogdf::edge e_sk = skel_edge_at(*spqr, e_up, parent);
-        return sk.original(e_sk->source()) == bB || sk.original(e_sk->target()) == bB;
+        linked[i] = 1u;
+        return pair_slots[pair_key(e)]++ == 0;
     }
 
-    ogdf::node BlockInfo::kth_ancestor(ogdf::node v, int k) const
+    // Returns whether the simple graph lost an edge.
+    bool PlanarSubgraph::Impl::unlink(const std::size_t i)
     {
-        if (!v || k < 0)
+        if (!linked[i])
         {
-            return nullptr;
+            return false;
         }
-        for (int i = 0; v && k; ++i)
+        linked[i] = 0u;
+        auto it = pair_slots.find(pair_key(edges_all()[i]));
+        if (--it->second == 0)
         {
-            if (k & 1)
+            pair_slots.erase(it);
+            return true;
+        }
+        return false;
+    }
+
+    std::size_t PlanarSubgraph::Impl::vertex_count() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return num_verts;
+    }
+
+    bool PlanarSubgraph::Impl::is_vertex(const std::size_t v) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return v < num_verts && !removed_verts[v];
+    }
+
+    std::size_t PlanarSubgraph::Impl::num_edges() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return edges_all().size();
+    }
+
+    std::vector<Edge> PlanarSubgraph::Impl::edges() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return edges_all();
+    }
+
+    Edge PlanarSubgraph::Impl::edge(const std::size_t edge_id) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return edges_all().at(edge_id);
+    }
+
+    bool PlanarSubgraph::Impl::is_candidate(const std::size_t edge_id) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return edge_id < removed.size() && !removed[edge_id];
+    }
+
+    bool PlanarSubgraph::Impl::is_selected(const std::size_t edge_id) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return edge_id < edges_added.size() && edges_added[edge_id];
+    }
+
+    std::uint8_t PlanarSubgraph::Impl::state(const std::size_t edge_id) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (edge_id >= states.size())
+        {
+            throw std::out_of_range("edge_id out of range");
+        }
+        return states[edge_id];
+    }
+
+    std::size_t PlanarSubgraph::Impl::num_required() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return required;
+    }
+
+    // Outside a transaction, a selection that is not addable is refused if
+    // a required candidate lies in one of the blocks it would merge, as the
+    // block it would make is not planar; inside one, `check_commit` does
+    // that.
+    std::uint8_t PlanarSubgraph::Impl::check_set(const std::size_t edge_id, const bool present)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (edge_id >= removed.size() || removed[edge_id])
+        {
+            throw std::out_of_range("edge_id does not name a candidate");
+        }
+        if (present == !!edges_added[edge_id])
+        {
+            return PlanarSubgraph::OK;
+        }
+        if (states[edge_id] == (present ? FORBIDDEN : REQUIRED))
+        {
+            return present ? PlanarSubgraph::IS_FORBIDDEN : PlanarSubgraph::IS_REQUIRED;
+        }
+        if (present && !txn_saved && required && !resolve(edge_id))
+        {
+            std::unordered_set<std::size_t> merged;
+            for (const BlockForest::Step &step : forest.path(edges_all()[edge_id].u, edges_all()[edge_id].v))
             {
-                v = up[i][v];
+                merged.insert(step.block);
+            }
+            for (size_t i = 0; i < states.size(); ++i)
+            {
+                const auto [a, b] = pair_key(edges_all()[i]);
+                if (!removed[i] && states[i] == REQUIRED && a != b && merged.count(forest.common_block(a, b)))
+                {
+                    return PlanarSubgraph::NOT_ADDABLE;
+                }
             }
-            k >>= 1;
         }
-        return v;
+        return PlanarSubgraph::OK;
     }
 
-    bool BlockInfo::is_ancestor(ogdf::node a, ogdf::node b) const
+    std::uint8_t PlanarSubgraph::Impl::check_commit() const
     {
-        return tin[a] <= tin[b] && tout[b] <= tout[a];
+        std::lock_guard<std::mutex> lock(mu);
+        if (!txn_saved)
+        {
+            return PlanarSubgraph::NO_TRANSACTION;
+        }
+        if (!required)
+        {
+            return PlanarSubgraph::OK;
+        }
+        std::vector<BlockForest::VertexPair> pairs;
+        for (size_t i = 0; i < edges_added.size(); ++i)
+        {
+            if (edges_added[i] && edges_all()[i].u != edges_all()[i].v)
+            {
+                pairs.push_back(pair_key(edges_all()[i]));
+            }
+        }
+        std::sort(pairs.begin(), pairs.end());
+        pairs.erase(std::unique(pairs.begin(), pairs.end()), pairs.end());
+        BlockForest after;
+        after.build(num_verts, pairs);
+        std::set<std::size_t> guarded;
+        for (size_t i = 0; i < states.size(); ++i)
+        {
+            const auto [a, b] = pair_key(edges_all()[i]);
+            if (!removed[i] && states[i] == REQUIRED && a != b)
+            {
+                guarded.insert(after.common_block(a, b));
+            }
+        }
+        for (size_t b : guarded)
+        {
+            if (!planar_pairs(after.block(b).edges))
+            {
+                return PlanarSubgraph::NOT_PLANAR;
+            }
+        }
+        return PlanarSubgraph::OK;
     }
 
-    bool BlockInfo::is_on_path_up_to_lca(ogdf::node x, ogdf::node start, ogdf::node lca) const
+    std::size_t PlanarSubgraph::Impl::evaluations() const
     {
-        return is_ancestor(x, start) && is_ancestor(lca, x);
+        std::lock_guard<std::mutex> lock(mu);
+        return evaluated;
     }
 
-    void BlockInfo::build_lca()
+    // Tests the recorded selection, so an open transaction's changes count.
+    bool PlanarSubgraph::Impl::is_planar() const
     {
-        const ogdf::Graph &T = *tree;
-        parent_t.init(T, nullptr);
-        parent_edge_t.init(T, nullptr);
-        depth_t.init(T, 0);
+        std::lock_guard<std::mutex> lock(mu);
+        std::vector<std::size_t> ids;
+        for (size_t i = 0; i < edges_added.size(); ++i)
+        {
+            if (edges_added[i])
+            {
+                ids.push_back(i);
+            }
+        }
+        return kuratowski_among(edges_all(), ids).empty();
+    }
 
-        ogdf::node root = spqr->rootNode();
+    bool PlanarSubgraph::Impl::in_transaction() const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return txn_saved.has_value();
+    }
 
-        std::vector<ogdf::node> st{root};
-        st.reserve(T.numberOfNodes());
-        parent_t[root] = nullptr;
-        parent_edge_t[root] = nullptr;
+    std::size_t PlanarSubgraph::Impl::add_candidate(const Edge &e)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot add a candidate during a transaction");
+        }
+        if (e.u >= num_verts || e.v >= num_verts)
+        {
+            throw std::out_of_range("edge endpoint index out of range");
+        }
+        if (removed_verts[e.u] || removed_verts[e.v])
+        {
+            throw std::invalid_argument("edge endpoint was removed");
+        }
+        if (candidates.use_count() != 1)
+        {
+            candidates = std::make_shared<std::vector<Edge>>(*candidates);
+        }
+        candidates->push_back(e);
+        removed.push_back(0u);
+        states.push_back(FREE);
+        edges_added.push_back(0u);
+        addable.push_back(UNKNOWN);
+        checked_at.push_back(0);
+        if (materialized)
+        {
+            linked.push_back(0u);
+        }
+        addable_snap.reset();
+        return edges_all().size() - 1;
+    }
 
-        while (!st.empty())
+    // Only a selected candidate's removal changes `forest`; otherwise the
+    // candidate itself is all that can flip.
+    void PlanarSubgraph::Impl::remove_candidate(const std::size_t edge_id, std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot remove a candidate during a transaction");
+        }
+        if (edge_id >= removed.size() || removed[edge_id])
+        {
+            throw std::out_of_range("edge_id does not name a candidate");
+        }
+        if (states[edge_id] == REQUIRED)
+        {
+            throw std::logic_error("cannot remove a required candidate");
+        }
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
+        {
+            watched = watch(edges_added[edge_id] ? std::vector<std::size_t>{edge_id} : std::vector<std::size_t>{},
+                            {edge_id});
+        }
+        removed[edge_id] = 1u;
+        addable[edge_id] = 0u;
+        addable_snap.reset();
+        if (edges_added[edge_id])
+        {
+            edges_added[edge_id] = 0u;
+            apply({edge_id});
+        }
+        if (flipped)
+        {
+            *flipped = flipped_since(watched);
+        }
+    }
+
+    // A new vertex is isolated, so no candidate's answer changes.
+    std::size_t PlanarSubgraph::Impl::add_vertex()
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot add a vertex during a transaction");
+        }
+        removed_verts.push_back(0u);
+        if (materialized)
+        {
+            forest.add_vertex();
+        }
+        return num_verts++;
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::Impl::remove_vertex(const std::size_t v, std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot remove a vertex during a transaction");
+        }
+        if (v >= num_verts || removed_verts[v])
+        {
+            throw std::out_of_range("vertex does not exist");
+        }
+        for (size_t i = 0; i < edges_all().size(); ++i)
+        {
+            auto [a, b] = edges_all()[i];
+            if (!removed[i] && states[i] == REQUIRED && (a == v || b == v))
+            {
+                throw std::logic_error("cannot remove a vertex with a required candidate");
+            }
+        }
+
+        std::vector<std::size_t> retired, changed;
+        for (size_t i = 0; i < edges_all().size(); ++i)
+        {
+            auto [a, b] = edges_all()[i];
+            if (!removed[i] && (a == v || b == v))
+            {
+                retired.push_back(i);
+                if (edges_added[i])
+                {
+                    changed.push_back(i);
+                }
+            }
+        }
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
+        {
+            watched = watch(changed, retired);
+        }
+
+        removed_verts[v] = 1u;
+        for (size_t i : retired)
+        {
+            removed[i] = 1u;
+            addable[i] = 0u;
+        }
+        for (size_t i : changed)
+        {
+            edges_added[i] = 0u;
+        }
+        if (!retired.empty())
+        {
+            addable_snap.reset();
+        }
+        apply(changed);
+        return retired;
+    }
+
+    // Only candidates at either endpoint change. They are taken out of
+    // `forest` under their old endpoints, moved over, and those left after
+    // merging parallels and dropping loops go back in. Only what the
+    // contraction itself makes parallel or a loop goes: groups at `keep`
+    // that were parallel already, and its loops, stay as they were. The
+    // removed endpoint stays behind isolated, like any removed vertex.
+    std::uint8_t PlanarSubgraph::Impl::contract(const std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                                                std::vector<std::size_t> &candidate_map,
+                                                std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            throw std::logic_error("cannot contract during a transaction");
+        }
+        if (edge_id >= removed.size() || removed[edge_id])
+        {
+            throw std::out_of_range("edge_id does not name a candidate");
+        }
+        if (!edges_added[edge_id])
+        {
+            return PlanarSubgraph::NOT_SELECTED;
+        }
+        const auto [keep, gone] = pair_key(edges_all()[edge_id]);
+        // candidates the contraction moves, by their old endpoints; a loop
+        // `edge_id` moves nothing else
+        auto moves = [&](std::size_t i)
+        {
+            auto [a, b] = edges_all()[i];
+            return i == edge_id || (keep != gone && (a == gone || b == gone));
+        };
+        // a required candidate must stay selected, so none may be dropped as
+        // a loop: not `edge_id`, nor its parallels, nor a loop at `gone`
+        auto merged = [&](std::size_t x) { return x == keep || x == gone; };
+        for (size_t i = 0; i < edges_all().size(); ++i)
+        {
+            auto [a, b] = edges_all()[i];
+            if (!removed[i] && states[i] == REQUIRED && merged(a) && merged(b) && moves(i))
+            {
+                candidate_map.assign(1, i);
+                return PlanarSubgraph::IS_REQUIRED;
+            }
+        }
+        std::vector<std::size_t> touched, was_selected;
+        for (size_t i = 0; i < edges_all().size(); ++i)
         {
-            ogdf::node v = st.back();
-            st.pop_back();
-            for (ogdf::adjEntry a = v->firstAdj(); a; a = a->succ())
+            auto [a, b] = edges_all()[i];
+            if (!removed[i] && (a == keep || b == keep || a == gone || b == gone))
             {
-                ogdf::edge e = a->theEdge();
-                ogdf::node w = a->twinNode();
-                if (w == parent_t[v])
+                touched.push_back(i);
+                if (edges_added[i])
                 {
-                    continue;
+                    was_selected.push_back(i);
+                }
+            }
+        }
+        // the pairs of the groups to merge, and the loops to drop
+        std::set<std::pair<std::size_t, std::size_t>> merging;
+        std::vector<std::size_t> dropped;
+        for (size_t i : touched)
+        {
+            if (moves(i))
+            {
+                auto [a, b] = edges_all()[i];
+                const auto key = std::minmax(a == gone ? keep : a, b == gone ? keep : b);
+                if (key.first == key.second)
+                {
+                    dropped.push_back(i);
+                }
+                else
+                {
+                    merging.insert(key);
                 }
-                parent_t[w] = v;
-                parent_edge_t[w] = e;
-                depth_t[w] = depth_t[v] + 1;
-                st.push_back(w);
             }
         }
+        // the edges put back all join neighbours of the merged endpoints, so
+        // their paths only cross blocks that lose an edge
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
+        {
+            watched = watch(was_selected, touched);
+        }
+        for (size_t i : was_selected)
+        {
+            edges_added[i] = 0u;
+        }
+        apply(was_selected);
 
-        const int n = T.numberOfNodes();
-        int LOG = 1;
-        while ((1 << LOG) <= n)
+        if (candidates.use_count() != 1)
         {
-            ++LOG;
+            candidates = std::make_shared<std::vector<Edge>>(*candidates);
         }
-        up.clear();
-        up.reserve(LOG);
-        up.emplace_back(T, nullptr);
-        for (ogdf::node v = T.firstNode(); v; v = v->succ())
+        for (size_t i : touched)
         {
-            up[0][v] = parent_t[v];
+            Edge &e = (*candidates)[i];
+            e.u = e.u == gone ? keep : e.u;
+            e.v = e.v == gone ? keep : e.v;
         }
-        for (int k = 1; k < LOG; ++k)
+        if (keep != gone)
         {
-            up.emplace_back(T, nullptr);
-            for (ogdf::node v = T.firstNode(); v; v = v->succ())
+            removed_verts[gone] = 1u;
+        }
+
+        candidate_map.assign(edges_all().size(), SIZE_MAX);
+        for (size_t i = 0; i < edges_all().size(); ++i)
+        {
+            if (!removed[i])
             {
-                ogdf::node mid = up[k - 1][v];
-                up[k][v] = mid ? up[k - 1][mid] : nullptr;
+                candidate_map[i] = i;
             }
         }
-
-        tin.init(T, 0);
-        tout.init(T, 0);
-        int timer = 0;
-        std::vector<std::pair<ogdf::node, ogdf::adjEntry>> dfsSt;
-        dfsSt.reserve(T.numberOfNodes());
-        tin[root] = ++timer;
-        dfsSt.emplace_back(root, root->firstAdj());
-        while (!dfsSt.empty())
+        // `touched` is increasing, so each group's survivor is its lowest id
+        std::map<std::pair<std::size_t, std::size_t>, std::size_t> survivor;
+        std::vector<std::size_t> reselect;
+        for (size_t i : touched)
         {
-            auto &fr = dfsSt.back();
-            ogdf::node v = fr.first;
-            ogdf::adjEntry &it = fr.second;
-            while (it && parent_t[it->twinNode()] != v)
+            const auto key = pair_key(edges_all()[i]);
+            const bool selected = std::binary_search(was_selected.begin(), was_selected.end(), i);
+            if (std::binary_search(dropped.begin(), dropped.end(), i))
             {
-                it = it->succ();
+                removed[i] = 1u;
+                addable[i] = 0u;
+                candidate_map[i] = SIZE_MAX;
+                continue;
             }
-            if (it)
+            auto [it, fresh] = merging.count(key) ? survivor.try_emplace(key, i) : std::make_pair(survivor.end(), true);
+            if (fresh)
             {
-                ogdf::node w = it->twinNode();
-                it = it->succ();
-                tin[w] = ++timer;
-                dfsSt.emplace_back(w, w->firstAdj());
+                addable[i] = UNKNOWN;
+                if (selected)
+                {
+                    reselect.push_back(i);
+                }
+                continue;
             }
-            else
+            removed[i] = 1u;
+            addable[i] = 0u;
+            // the survivor is required if any member was, and forbidden only
+            // if all were
+            std::uint8_t &kept = states[it->second];
+            if (states[i] == REQUIRED && kept == REQUIRED)
             {
-                tout[v] = timer;
-                dfsSt.pop_back();
+                --required;
+            }
+            if (states[i] == REQUIRED || (kept == FORBIDDEN && states[i] == FREE))
+            {
+                kept = states[i];
+            }
+            candidate_map[i] = it->second;
+            if (selected)
+            {
+                reselect.push_back(it->second);
             }
         }
-    }
+        std::sort(reselect.begin(), reselect.end());
+        reselect.erase(std::unique(reselect.begin(), reselect.end()), reselect.end());
+        for (size_t i : reselect)
+        {
+            edges_added[i] = 1u;
+        }
+        apply(reselect);
+        addable_snap.reset();
 
-    ogdf::node BlockInfo::lca(ogdf::node a, ogdf::node b) const
-    {
-        if (a == b)
+        vertex_map.resize(num_verts);
+        for (size_t v = 0; v < num_verts; ++v)
         {
-            return a;
+            vertex_map[v] = v == gone ? keep : v;
         }
-        int da = depth_t[a], db = depth_t[b];
-        if (da < db)
+        if (flipped)
         {
-            std::swap(a, b), std::swap(da, db);
+            *flipped = flipped_since(watched);
         }
-        int diff = da - db;
-        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        return PlanarSubgraph::OK;
+    }
+
+    // Requiring a candidate selects it, and forbidding one deselects it; the
+    // rest see that through `apply` as after `set`. Otherwise only the
+    // candidate's own answer can change.
+    bool PlanarSubgraph::Impl::set_state(const std::size_t edge_id, const std::uint8_t state,
+                                         std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
         {
-            if ((diff >> k) & 1)
-            {
-                a = up[k][a];
-            }
+            throw std::logic_error("cannot change a candidate state during a transaction");
         }
-        if (a == b)
+        if (edge_id >= removed.size() || removed[edge_id])
         {
-            return a;
+            throw std::out_of_range("edge_id does not name a candidate");
         }
-        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+        if (state > FORBIDDEN)
         {
-            if (up[k][a] != up[k][b])
+            throw std::invalid_argument("unknown candidate state");
+        }
+
+        const bool select = state == REQUIRED && !edges_added[edge_id];
+        const bool deselect = state == FORBIDDEN && edges_added[edge_id];
+        if (select)
+        {
+            // a forbidden candidate's answer is pinned to 0; work it out
+            const std::uint8_t was = states[edge_id];
+            states[edge_id] = FREE;
+            const bool ok = resolve(edge_id);
+            states[edge_id] = was;
+            if (!ok)
             {
-                a = up[k][a];
-                b = up[k][b];
+                return false;
             }
         }
-        return parent_t[a];
-    }
 
-    Mask BlockInfo::endpoint_mask_at(ogdf::node v_t, ogdf::edge up_edge, ogdf::node uB)
-    {
-        if (!up_edge)
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
         {
-            return 0;
+            watched = watch(select || deselect ? std::vector<std::size_t>{edge_id} : std::vector<std::size_t>{},
+                            {edge_id});
         }
-        if (spqr->typeOf(v_t) != ogdf::SPQRTree::NodeType::RNode)
+        if (select || deselect)
         {
-            return 3;
+            edges_added[edge_id] = select ? 1u : 0u;
+            apply({edge_id});
         }
-        auto ef = edge_face_ids(v_t, up_edge);
-        const auto &vf = vertex_face_ids(v_t, uB);
-        Mask m = 0;
-        if (contains_face_id(vf, ef[0]))
+        if (state == REQUIRED && states[edge_id] != REQUIRED)
         {
-            m |= 1;
+            ++required;
         }
-        if (contains_face_id(vf, ef[1]))
+        else if (state != REQUIRED && states[edge_id] == REQUIRED)
         {
-            m |= 2;
+            --required;
         }
-        return m;
+        states[edge_id] = state;
+        addable_snap.reset();
+        if (flipped)
+        {
+            *flipped = flipped_since(watched);
+        }
+        return true;
     }
 
-    std::pair<ogdf::edge, Mask> BlockInfo::walk_side_to_lca(ogdf::node start_t, ogdf::node lca_t,
-                                                            ogdf::node endpoint_b)
+    void PlanarSubgraph::Impl::set(const std::size_t edge_id, const bool present, std::vector<std::size_t> *flipped)
     {
-        if (start_t == lca_t)
+        std::lock_guard<std::mutex> lock(mu);
+        size_t i = static_cast<size_t>(edge_id);
+        if (i >= edges_added.size() || removed[i])
         {
-            return {nullptr, 0};
+            throw std::out_of_range("edge_id does not name a candidate");
         }
 
-        ogdf::edge e_up = parent_edge_t[start_t];
-        ogdf::node parent_at_boundary = parent_t[start_t];
-
-        Mask seed_mask = endpoint_mask_at(parent_at_boundary, e_up, endpoint_b);
-        if (seed_mask == 0 && spqr->typeOf(parent_at_boundary) == ogdf::SPQRTree::NodeType::RNode)
+        if (present == !!edges_added[i])
         {
-            seed_mask = 3;
+            return;
         }
-        if (!seed_mask)
+        if (states[i] == (present ? FORBIDDEN : REQUIRED))
         {
-            return {e_up, 0};
+            throw std::logic_error("candidate state does not allow this change");
         }
 
-        int steps_to_lca = depth_t[parent_at_boundary] - depth_t[lca_t];
-        if (steps_to_lca == 0)
+        if (txn_saved)
         {
-            return {e_up, seed_mask};
+            edges_added[i] = present ? 1u : 0u;
+            txn_touched.push_back(i);
+            return;
+        }
+        if (!flipped)
+        {
+            edges_added[i] = present ? 1u : 0u;
+            apply({i});
+            return;
         }
+        auto watched = watch({i});
+        edges_added[i] = present ? 1u : 0u;
+        apply({i});
+        *flipped = flipped_since(watched);
+    }
 
-        Mask mask = seed_mask;
-        ogdf::node child = start_t;
-        for (int s = 0; s < steps_to_lca; ++s)
+    // Greedy in batches: candidates not addable now never will be, as the
+    // selection only grows, and a batch of addable ones that keeps the
+    // selection planar is exactly what a `set` loop would select. A batch
+    // that does not is cut to its longest planar prefix, and the fill goes
+    // on from the candidate that broke it. Each batch is one update, so a
+    // block's SPQR tree is rebuilt once per batch that changes it rather
+    // than once per selection, and batches double while they succeed.
+    std::vector<std::size_t> PlanarSubgraph::Impl::fill_greedy(const std::vector<std::size_t> &order,
+                                                               std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
         {
-            ogdf::node cur = parent_t[child];
-            if (cur == lca_t)
+            throw std::logic_error("cannot fill during a transaction");
+        }
+        for (size_t i : order)
+        {
+            if (i >= edges_added.size() || removed[i])
             {
-                break;
+                throw std::out_of_range("edge_id does not name a candidate");
             }
+        }
 
-            if (spqr->typeOf(cur) == ogdf::SPQRTree::NodeType::RNode && mask)
+        // only the candidates of `order` can be selected
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
+        {
+            std::vector<std::size_t> open;
+            for (size_t i : order)
             {
-                mask = 3;
+                if (!edges_added[i])
+                {
+                    open.push_back(i);
+                }
             }
+            std::sort(open.begin(), open.end());
+            open.erase(std::unique(open.begin(), open.end()), open.end());
+            watched = watch(open);
+        }
 
-            if (spqr->typeOf(cur) == ogdf::SPQRTree::NodeType::RNode)
+        std::vector<std::size_t> selected;
+        std::size_t want = 1;
+        for (size_t pos = 0; pos < order.size();)
+        {
+            // the next `want` candidates addable on their own, and where
+            // the fill goes on after each
+            std::vector<std::size_t> batch, after;
+            std::unordered_set<std::size_t> taken;
+            for (; pos < order.size() && batch.size() < want; ++pos)
             {
-                auto fin = edge_face_ids(cur, parent_edge_t[child]);
-                auto fout = edge_face_ids(cur, parent_edge_t[cur]);
-                Mask next_mask = 0;
-                if (mask & 1)
+                const std::size_t i = order[pos];
+                if (!edges_added[i] && !taken.count(i) && resolve(i))
                 {
-                    if (fin[0] == fout[0])
-                    {
-                        next_mask |= 1;
-                    }
-                    if (fin[0] == fout[1])
-                    {
-                        next_mask |= 2;
-                    }
+                    taken.insert(i);
+                    batch.push_back(i);
+                    after.push_back(pos + 1);
                 }
-                if (mask & 2)
+            }
+            if (batch.empty())
+            {
+                break;
+            }
+
+            std::size_t keep = batch.size();
+            if (batch_planar(batch))
+            {
+                want *= 2;
+            }
+            else
+            {
+                // the empty prefix is planar and the whole batch is not
+                std::size_t lo = 0, hi = batch.size();
+                while (hi - lo > 1)
                 {
-                    if (fin[1] == fout[0])
+                    const std::size_t mid = lo + (hi - lo) / 2;
+                    if (batch_planar({batch.begin(), batch.begin() + mid}))
                     {
-                        next_mask |= 1;
+                        lo = mid;
                     }
-                    if (fin[1] == fout[1])
+                    else
                     {
-                        next_mask |= 2;
+                        hi = mid;
                     }
                 }
-                mask = next_mask;
+                // a first candidate that is addable yet fails on its own
+                // meets a block that is already not planar, where `resolve`
+                // has the final say; anything later is looked at again
+                keep = std::max<std::size_t>(lo, 1);
+                pos = lo ? after[lo - 1] : after[0];
+                want = std::max<std::size_t>(want / 2, 1);
             }
-            if (!mask)
+            batch.resize(keep);
+            for (size_t i : batch)
             {
-                break;
+                edges_added[i] = 1u;
             }
-            child = cur;
+            apply(batch);
+            selected.insert(selected.end(), batch.begin(), batch.end());
         }
-        if (!mask)
+
+        if (flipped)
         {
-            return {e_up, 0};
+            *flipped = flipped_since(watched);
         }
-
-        ogdf::node child_below_lca = kth_ancestor(parent_at_boundary, steps_to_lca - 1);
-        ogdf::edge incoming_at_lca = child_below_lca ? parent_edge_t[child_below_lca] : e_up;
-        return {incoming_at_lca, mask};
+        return selected;
     }
 
-    ogdf::node BlockInfo::walk_up_containing(ogdf::node start, ogdf::node stop, ogdf::node bB) const
+    // Whether the selection stays planar with `batch` added, as far as the
+    // blocks the batch may merge go: those on the paths between its new
+    // edges' endpoints, as in `watch`. Every other block is left as it is.
+    bool PlanarSubgraph::Impl::batch_planar(const std::vector<std::size_t> &batch)
     {
-        ogdf::node repr = repr_t[bB];
-        ogdf::node cur = start;
-        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
-        {
-            ogdf::node cand = up[k][cur];
-            if (!cand || depth_t[cand] < depth_t[stop])
+        refresh();
+        std::unordered_set<std::size_t> blocks;
+        std::unordered_map<std::size_t, std::size_t> hubs;
+        std::vector<std::pair<std::size_t, std::size_t>> pairs;
+        for (size_t i : batch)
+        {
+            const auto [u, v] = pair_key(edges_all()[i]);
+            if (u == v || pair_slots.count({u, v}))
             {
                 continue;
             }
-            if (repr == cand)
+            pairs.emplace_back(u, v);
+            for (size_t x : {u, v})
             {
-                cur = cand;
+                auto [hub, fresh] = hubs.try_emplace(forest.component(x), x);
+                if (!fresh)
+                {
+                    for (const BlockForest::Step &step : forest.path(hub->second, x))
+                    {
+                        blocks.insert(step.block);
+                    }
+                }
             }
-            else if (is_ancestor(cand, repr))
+        }
+        for (size_t b : blocks)
+        {
+            const auto &edges = forest.block(b).edges;
+            pairs.insert(pairs.end(), edges.begin(), edges.end());
+        }
+        return planar_pairs(std::move(pairs));
+    }
+
+    bool PlanarSubgraph::Impl::begin()
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (txn_saved)
+        {
+            return false;
+        }
+        txn_saved = edges_added;
+        return true;
+    }
+
+    bool PlanarSubgraph::Impl::commit(std::vector<std::size_t> *flipped)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (!txn_saved)
+        {
+            return false;
+        }
+        std::sort(txn_touched.begin(), txn_touched.end());
+        txn_touched.erase(std::unique(txn_touched.begin(), txn_touched.end()), txn_touched.end());
+        std::vector<std::size_t> changed;
+        for (size_t i : txn_touched)
+        {
+            if (edges_added[i] != (*txn_saved)[i])
+            {
+                changed.push_back(i);
+            }
+        }
+        // `watch` must still see the selection at `begin`
+        std::vector<std::pair<std::size_t, bool>> watched;
+        if (flipped)
+        {
+            watched = watch(changed);
+        }
+        txn_saved.reset();
+        txn_touched.clear();
+        apply(changed);
+        if (flipped)
+        {
+            *flipped = flipped_since(watched);
+        }
+        return true;
+    }
+
+    bool PlanarSubgraph::Impl::rollback()
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (!txn_saved)
+        {
+            return false;
+        }
+        // `forest` and `addable` were left alone since `begin`.
+        edges_added = std::move(*txn_saved);
+        txn_saved.reset();
+        txn_touched.clear();
+        return true;
+    }
+
+    std::vector<std::uint8_t> PlanarSubgraph::Impl::query()
+    {
+        std::size_t len = 0;
+        auto snap = snapshot(len);
+        std::vector<std::uint8_t> out(len);
+        for (size_t i = 0; i < len; ++i)
+        {
+            out[i] = static_cast<std::uint8_t>(((*snap)[i / 64] >> (i % 64)) & 1u);
+        }
+        return out;
+    }
+
+    std::shared_ptr<const std::vector<std::uint64_t>> PlanarSubgraph::Impl::snapshot(std::size_t &len)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (!addable_snap)
+        {
+            std::vector<std::uint64_t> words((addable.size() + 63) / 64, 0u);
+            for (size_t i = 0; i < addable.size(); ++i)
             {
-                const int dist = depth_t[repr] - depth_t[cand];
-                ogdf::node child = kth_ancestor(repr, dist - 1);
-                if (child && edge_contains_b(cand, child, bB))
+                if (resolve(i))
                 {
-                    cur = cand;
+                    words[i / 64] |= std::uint64_t{1} << (i % 64);
                 }
             }
+            addable_snap = std::make_shared<const std::vector<std::uint64_t>>(std::move(words));
         }
-        return cur;
+        len = addable.size();
+        return addable_snap;
     }
 
-    ogdf::node BlockInfo::walk_down_containing(ogdf::node from, ogdf::node toward, ogdf::node bB) const
+    bool PlanarSubgraph::Impl::is_addable(const std::size_t edge_id)
     {
-        if (!from || !toward)
-        {
-            return from;
-        }
-        int dist = depth_t[toward] - depth_t[from];
-        if (dist <= 0)
+        std::lock_guard<std::mutex> lock(mu);
+        if (edge_id >= addable.size())
         {
-            return from;
+            throw std::out_of_range("edge_id out of range");
         }
+        return resolve(edge_id);
+    }
 
-        for (int k = static_cast<int>(up.size()) - 1; k >= 0; --k)
+    std::vector<std::uint8_t> PlanarSubgraph::Impl::addable_among(const std::vector<std::size_t> &edge_ids)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        std::vector<std::uint8_t> out;
+        out.reserve(edge_ids.size());
+        for (size_t i : edge_ids)
         {
-            int step = 1 << k;
-            if (step > dist)
-            {
-                continue;
-            }
-
-            ogdf::node child = kth_ancestor(toward, dist - step);
-            ogdf::node parent = parent_t[child];
-            if (edge_contains_b(parent, child, bB))
+            if (i >= addable.size())
             {
-                dist -= step;
+                throw std::out_of_range("edge_id out of range");
             }
+            out.push_back(resolve(i) ? 1u : 0u);
         }
-        return kth_ancestor(toward, dist);
+        return out;
     }
 
-    std::pair<ogdf::node, ogdf::node> BlockInfo::compute_boundaries(ogdf::node aT, ogdf::node bT, ogdf::node w,
-                                                                    ogdf::node aB, ogdf::node bB) const
+    // Only the candidate's connected component can take part in the
+    // obstruction, so the planarity test runs on that alone.
+    bool PlanarSubgraph::Impl::obstruction(const std::size_t edge_id, std::vector<std::size_t> &witness_ids)
     {
-        ogdf::node aBoundary = walk_up_containing(aT, w, aB);
-        if (aBoundary == w)
+        std::lock_guard<std::mutex> lock(mu);
+        witness_ids.clear();
+        if (edge_id >= removed.size() || removed[edge_id] || resolve(edge_id) || applied()[edge_id])
         {
-            aBoundary = walk_down_containing(w, bT, aB);
+            return false;
         }
 
-        ogdf::node bBoundary = walk_up_containing(bT, w, bB);
-        if (bBoundary == w)
+        const std::size_t c = forest.component(edges_all()[edge_id].u);
+        std::vector<std::size_t> ids{edge_id};
+        for (size_t i = 0; i < linked.size(); ++i)
         {
-            bBoundary = walk_down_containing(w, aT, bB);
+            if (linked[i] && forest.component(edges_all()[i].u) == c)
+            {
+                ids.push_back(i);
+            }
         }
-
-        return {aBoundary, bBoundary};
+        witness_ids = kuratowski_among(edges_all(), ids);
+        return !witness_ids.empty();
     }
 
-    inline bool share_faces(std::pair<ogdf::face, ogdf::face> A, std::pair<ogdf::face, ogdf::face> B)
+    std::vector<std::size_t> PlanarSubgraph::Impl::conflicts(const std::size_t edge_id)
     {
-        return A.first == B.first || A.first == B.second || A.second == B.first || A.second == B.second;
+        std::lock_guard<std::mutex> lock(mu);
+        if (edge_id >= removed.size())
+        {
+            throw std::out_of_range("edge_id out of range");
+        }
+        if (!resolve(edge_id))
+        {
+            return {};
+        }
+        return speculate_conflicts(edge_id);
     }
 
-    bool BlockInfo::cofacial_at_node(ogdf::node vT, ogdf::node aB, ogdf::node bB)
+    // Deletion filter over the batch: a member stays only if the rest of the
+    // batch is planar with the selection without it. Every test also narrows
+    // the batch to the Kuratowski subgraph it found, so most members are
+    // dropped without a test of their own.
+    bool PlanarSubgraph::Impl::can_add_all(const std::vector<std::size_t> &edge_ids,
+                                           std::vector<std::size_t> &conflict) const
     {
-        if (spqr->typeOf(vT) != ogdf::SPQRTree::NodeType::RNode)
+        std::lock_guard<std::mutex> lock(mu);
+        conflict.clear();
+        for (size_t i : edge_ids)
         {
-            return true;
+            if (i >= removed.size() || removed[i])
+            {
+                throw std::out_of_range("edge_id does not name a candidate");
+            }
         }
-        const auto &A = vertex_face_ids(vT, aB);
-        const auto &B = vertex_face_ids(vT, bB);
-        size_t i = 0, j = 0;
-        while (i < A.size() && j < B.size())
+        // a forbidden candidate conflicts all on its own
+        for (size_t i : edge_ids)
         {
-            if (A[i] < B[j])
+            if (states[i] == FORBIDDEN)
             {
-                ++i;
+                conflict.push_back(i);
+                return false;
             }
-            else if (A[i] > B[j])
+        }
+
+        std::vector<std::size_t> base;
+        std::set<std::pair<std::size_t, std::size_t>> pairs;
+        for (size_t i = 0; i < applied().size(); ++i)
+        {
+            if (applied()[i])
             {
-                ++j;
+                base.push_back(i);
+                pairs.insert(pair_key(edges_all()[i]));
             }
-            else
+        }
+        // loops and parallels of a selected or earlier batch edge change
+        // nothing, so they never take part in a conflict
+        std::vector<std::size_t> batch;
+        for (size_t i : edge_ids)
+        {
+            const auto key = pair_key(edges_all()[i]);
+            if (key.first != key.second && pairs.insert(key).second)
             {
-                return true;
+                batch.push_back(i);
             }
         }
-        return false;
-    }
 
-    bool BlockInfo::block_linkable(ogdf::node hA, ogdf::node hB)
-    {
-        if (!spqr)
+        // whether the selection plus `members` without `skip` is non-planar,
+        // and the members in the Kuratowski subgraph found if so
+        auto obstructing = [&](const std::vector<std::size_t> &members, std::optional<std::size_t> skip)
         {
-            return true;
-        }
+            std::vector<std::size_t> ids = base;
+            for (size_t i : members)
+            {
+                if (i != skip)
+                {
+                    ids.push_back(i);
+                }
+            }
+            std::vector<std::size_t> witness = kuratowski_among(edges_all(), ids);
+            std::vector<std::size_t> out;
+            for (size_t i : members)
+            {
+                if (i != skip && std::binary_search(witness.begin(), witness.end(), i))
+                {
+                    out.push_back(i);
+                }
+            }
+            return std::make_pair(!witness.empty(), out);
+        };
 
-        ogdf::node aB = h_to_b[hA];
-        ogdf::node bB = h_to_b[hB];
-        if (!aB || !bB)
+        auto [blocked, narrowed] = obstructing(batch, std::nullopt);
+        if (!blocked)
         {
-            return false;
+            return true;
         }
-        if (aB == bB)
+        batch = std::move(narrowed);
+        for (size_t k = 0; k < batch.size();)
         {
-            return true;
+            auto [still_blocked, rest] = obstructing(batch, batch[k]);
+            if (!still_blocked)
+            {
+                ++k;
+                continue;
+            }
+            // `rest` keeps the members already found necessary, which all
+            // come before `batch[k]`
+            batch = std::move(rest);
         }
+        std::sort(batch.begin(), batch.end());
+        conflict = std::move(batch);
+        return false;
+    }
 
-        ogdf::node aT = repr_t[aB];
-        ogdf::node bT = repr_t[bB];
-        if (!aT || !bT)
+    bool PlanarSubgraph::Impl::embedding(std::vector<std::size_t> &rotation_offsets,
+                                         std::vector<std::size_t> &rotation) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        return embed_applied(std::nullopt, nullptr, rotation_offsets, rotation);
+    }
+
+    // Embedding the selection together with the candidate and then dropping
+    // the candidate leaves its endpoints on a common face.
+    bool PlanarSubgraph::Impl::witness_embedding(const std::size_t edge_id,
+                                                 std::vector<std::size_t> &rotation_offsets,
+                                                 std::vector<std::size_t> &rotation)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        rotation_offsets.clear();
+        rotation.clear();
+        if (edge_id >= removed.size() || !resolve(edge_id))
         {
             return false;
         }
+        return embed_applied(edge_id, nullptr, rotation_offsets, rotation);
+    }
 
-        BlockInfo::PairKey key{aB, bB};
-        if (auto it = memo.find(key); it != memo.end())
+    // `verts` sorted and deduplicated; throws if one is not a vertex
+    std::vector<std::size_t> PlanarSubgraph::Impl::attach_targets(const std::vector<std::size_t> &verts) const
+    {
+        for (size_t v : verts)
         {
-            return it->second;
+            if (v >= num_verts || removed_verts[v])
+            {
+                throw std::out_of_range("vertex does not exist");
+            }
         }
-        auto memo_return = [&](bool v)
-        {
-            memo.emplace(key, v);
-            return v;
-        };
+        std::vector<std::size_t> out = verts;
+        std::sort(out.begin(), out.end());
+        out.erase(std::unique(out.begin(), out.end()), out.end());
+        return out;
+    }
 
-        if (aT == bT)
+    // A hub joined to `verts` fits iff they share a face in some embedding.
+    // That needs each pair of them to be cofacial, which the block caches
+    // answer without a planarity test; for two vertices it is all there is
+    // to check. Larger sets that pass are settled by embedding the
+    // selection with the hub.
+    bool PlanarSubgraph::Impl::can_attach(const std::vector<std::size_t> &verts)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        const std::vector<std::size_t> targets = attach_targets(verts);
+        if (targets.size() < 2)
         {
-            return memo_return(cofacial_at_node(aT, aB, bB));
+            return true;
         }
-
-        ogdf::node w = lca(aT, bT);
-
-        auto [a_boundary, b_boundary] = compute_boundaries(aT, bT, w, aB, bB);
-        if (a_boundary != b_boundary)
+        refresh();
+        for (size_t a = 0; a < targets.size(); ++a)
         {
-            auto crossed = [&](ogdf::node path_start, ogdf::node first_b, ogdf::node second_b)
+            for (size_t b = a + 1; b < targets.size(); ++b)
             {
-                if (!is_on_path_up_to_lca(second_b, path_start, w))
+                if (!pair_addable(targets[a], targets[b]))
                 {
                     return false;
                 }
-                bool first_on = is_on_path_up_to_lca(first_b, path_start, w);
-                return !first_on || (depth_t[second_b] > depth_t[first_b]);
-            };
-            if (crossed(aT, a_boundary, b_boundary) || crossed(bT, b_boundary, a_boundary))
-            {
-                return memo_return(true);
             }
         }
+        if (targets.size() == 2)
+        {
+            return true;
+        }
+        std::vector<std::size_t> rotation_offsets, rotation;
+        return embed_applied(std::nullopt, &targets, rotation_offsets, rotation);
+    }
 
-        ogdf::node start_left = a_boundary;
-        ogdf::node start_right = b_boundary;
-        w = lca(start_left, start_right);
-
-        auto [left_into_w, left_mask] = walk_side_to_lca(start_left, w, aB);
-        auto [right_into_w, right_mask] = walk_side_to_lca(start_right, w, bB);
-
-        bool left_seed_ok = endpoint_mask_at(start_left, parent_edge_t[start_left], aB) != 0;
-        bool right_seed_ok = endpoint_mask_at(start_right, parent_edge_t[start_right], bB) != 0;
+    // Dropping the hub from an embedding that includes it leaves `verts` on
+    // the face it sat in.
+    bool PlanarSubgraph::Impl::attach_embedding(const std::vector<std::size_t> &verts,
+                                                std::vector<std::size_t> &rotation_offsets,
+                                                std::vector<std::size_t> &rotation) const
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        const std::vector<std::size_t> targets = attach_targets(verts);
+        return embed_applied(std::nullopt, &targets, rotation_offsets, rotation);
+    }
 
-        bool left_ok = (start_left == w) || (left_seed_ok && left_mask != 0);
-        bool right_ok = (start_right == w) || (right_seed_ok && right_mask != 0);
-        if (!left_ok || !right_ok)
+    // Embeddings are not derived from the SPQR trees, which only ever cover
+    // single blocks: each comes from its own Boyer-Myrvold pass over the
+    // applied selection, plus `extra` if given, plus a hub vertex joined to
+    // each of `hub` if given, and matches the addability answers only as far
+    // as those force it to. Darts of `extra` and of the hub are left out of
+    // the result.
+    bool PlanarSubgraph::Impl::embed_applied(const std::optional<std::size_t> extra,
+                                             const std::vector<std::size_t> *hub,
+                                             std::vector<std::size_t> &rotation_offsets,
+                                             std::vector<std::size_t> &rotation) const
+    {
+        std::vector<std::size_t> ids;
+        std::vector<Edge> sub;
+        for (size_t i = 0; i < applied().size(); ++i)
         {
-            return memo_return(false);
+            if (applied()[i] || extra == i)
+            {
+                ids.push_back(i);
+                sub.push_back(edges_all()[i]);
+            }
+        }
+        if (hub)
+        {
+            for (size_t s : *hub)
+            {
+                sub.push_back(Edge{s, num_verts});
+            }
         }
 
-        if (spqr->typeOf(w) != ogdf::SPQRTree::NodeType::RNode)
+        std::vector<std::size_t> witness;
+        if (!planar_embedding(num_verts + (hub ? 1 : 0), sub, rotation_offsets, rotation, witness))
         {
-            return memo_return(true);
+            rotation_offsets.clear();
+            rotation.clear();
+            return false;
         }
 
-        if (left_into_w && right_into_w)
+        size_t out = 0;
+        for (size_t v = 0; v < num_verts; ++v)
         {
-            auto &ce_w = embedding_r(w);
-            ogdf::edge e_sk_l = skel_edge_at(*spqr, left_into_w, w);
-            ogdf::edge e_sk_r = skel_edge_at(*spqr, right_into_w, w);
-            return memo_return(share_faces(faces_of(ce_w, e_sk_l), faces_of(ce_w, e_sk_r)));
+            const size_t begin = rotation_offsets[v];
+            rotation_offsets[v] = out;
+            for (size_t k = begin; k < rotation_offsets[v + 1]; ++k)
+            {
+                if (rotation[k] < ids.size() && extra != ids[rotation[k]])
+                {
+                    rotation[out++] = ids[rotation[k]];
+                }
+            }
         }
+        rotation_offsets[num_verts] = out;
+        rotation_offsets.resize(num_verts + 1);
+        rotation.resize(out);
+        return true;
+    }
 
-        if (left_into_w || right_into_w)
+    bool PlanarSubgraph::Impl::can_add(const std::size_t u, const std::size_t v)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (u >= num_verts || v >= num_verts || removed_verts[u] || removed_verts[v])
         {
-            ogdf::edge in_e = left_into_w ? left_into_w : right_into_w;
-            ogdf::node ep = left_into_w ? bB : aB;
-            return memo_return(endpoint_mask_at(w, in_e, ep) != 0);
+            throw std::out_of_range("vertex does not exist");
         }
+        refresh();
+        return pair_addable(u, v);
+    }
 
-        return memo_return(cofacial_at_node(w, aB, bB));
+    std::vector<std::size_t> PlanarSubgraph::Impl::addable_partners(const std::size_t v)
+    {
+        std::lock_guard<std::mutex> lock(mu);
+        if (v >= num_verts || removed_verts[v])
+        {
+            throw std::out_of_range("vertex does not exist");
+        }
+        refresh();
+        std::vector<std::size_t> out;
+        for (size_t w = 0; w < num_verts; ++w)
+        {
+            if (w != v && !removed_verts[w] && pair_addable(v, w))
+            {
+                out.push_back(w);
+            }
+        }
+        return out;
     }
 
-    void PlanarSubgraph::Impl::reset_blocks(const ogdf::Graph &bc_tree)
+    // Same test `resolve` runs for a candidate, for any pair of vertices.
+    // Expects `refresh` to have run.
+    bool PlanarSubgraph::Impl::pair_addable(const std::size_t u, const std::size_t v)
     {
-        owned_blocks.clear();
-        owned_blocks.reserve(bc_tree.numberOfNodes());
-        blocks = std::make_unique<ogdf::NodeArray<BlockInfo *>>(bc_tree, nullptr);
+        return u == v || forest.component(u) != forest.component(v) || pair_slots.count(std::minmax(u, v)) ||
+               can_add_along_bc(u, v);
     }
 
-    BlockInfo &PlanarSubgraph::Impl::get_or_build_block(const BCTreeX &bc, ogdf::node vB)
+    void PlanarSubgraph::Impl::conflict_graph(std::vector<std::size_t> &offsets, std::vector<std::size_t> &neighbours)
     {
-        BlockInfo *p = (*blocks)[vB];
-        if (p)
+        std::lock_guard<std::mutex> lock(mu);
+        offsets.assign(1, 0);
+        neighbours.clear();
+        for (size_t i = 0; i < addable.size(); ++i)
         {
-            return *p;
+            if (resolve(i))
+            {
+                auto out = speculate_conflicts(i);
+                neighbours.insert(neighbours.end(), out.begin(), out.end());
+            }
+            offsets.push_back(neighbours.size());
         }
-        owned_blocks.emplace_back(std::make_unique<BlockInfo>(bc, vB));
-        p = owned_blocks.back().get();
-        (*blocks)[vB] = p;
-        return *p;
     }
 
-    bool PlanarSubgraph::Impl::can_add_along_bc(const BCTreeX &bc, ogdf::node uG, ogdf::node vG)
+    // Which addable candidates selecting addable candidate `i` would block,
+    // worked out without touching `forest`. Within a component, `i` merges
+    // the blocks on its path into one and leaves the others alone; every
+    // block on an addable candidate's path can take it already, so one that
+    // crosses the merged blocks stays addable iff the merged block can take
+    // an edge between where its path enters and leaves them. Between
+    // components, `i` is a bridge, and a candidate joining the two stays
+    // addable iff each side can take an edge to `i`'s endpoint there.
+    std::vector<std::size_t> PlanarSubgraph::Impl::speculate_conflicts(const std::size_t i)
     {
-        ogdf::node uB = bc.bcproper(uG);
-        ogdf::node vB = bc.bcproper(vG);
-        if (!uB || !vB)
+        const auto key = pair_key(edges_all()[i]);
+        if (key.first == key.second || pair_slots.count(key))
         {
-            return true;
+            return {};
         }
+        const std::size_t u = key.first, v = key.second;
+        const bool bridge = forest.component(u) != forest.component(v);
 
-        ogdf::node w = bc.findNCA(uB, vB);
-        if (!w)
+        std::vector<std::size_t> parts;
+        std::unique_ptr<BlockInfo> merged;
+        if (!bridge)
         {
-            return true;
+            std::vector<std::pair<std::size_t, std::size_t>> edges{key};
+            for (const BlockForest::Step &step : forest.path(u, v))
+            {
+                parts.push_back(step.block);
+                const auto &own = forest.block(step.block).edges;
+                edges.insert(edges.end(), own.begin(), own.end());
+            }
+            merged = std::make_unique<BlockInfo>(edges);
+            std::sort(parts.begin(), parts.end());
+        }
+        auto merges = [&](const BlockForest::Step &step)
+        { return std::binary_search(parts.begin(), parts.end(), step.block); };
+
+        std::vector<std::size_t> out;
+        for (size_t j = 0; j < edges_all().size(); ++j)
+        {
+            const auto key_j = pair_key(edges_all()[j]);
+            if (removed[j] || key_j.first == key_j.second || key_j == key || pair_slots.count(key_j))
+            {
+                continue;
+            }
+            std::size_t a = key_j.first, b = key_j.second;
+            bool stays;
+            if (bridge)
+            {
+                if (forest.component(a) == forest.component(v))
+                {
+                    std::swap(a, b);
+                }
+                if (forest.component(a) != forest.component(u) || forest.component(b) != forest.component(v) ||
+                    !resolve(j))
+                {
+                    continue;
+                }
+                stays = (a == u || can_add_along_bc(a, u)) && (b == v || can_add_along_bc(v, b));
+            }
+            else
+            {
+                if (forest.component(a) != forest.component(u) || forest.component(b) != forest.component(u))
+                {
+                    continue;
+                }
+                const std::vector<BlockForest::Step> steps = forest.path(a, b);
+                auto first = std::find_if(steps.begin(), steps.end(), merges);
+                if (first == steps.end() || !resolve(j))
+                {
+                    continue;
+                }
+                auto last = std::find_if(steps.rbegin(), steps.rend(), merges);
+                stays = merged->block_linkable(first->x, last->y);
+            }
+            if (!stays)
+            {
+                out.push_back(j);
+            }
         }
+        return out;
+    }
 
-        auto process_branch = [&](ogdf::node start, ogdf::node lca, ogdf::node end_g,
-                                  bool is_left) -> std::pair<bool, ogdf::node>
+    // Candidates whose addability applying `changed` could flip, with their
+    // current answers, plus those of `also` (sorted), which the caller
+    // changes some other way. An answer depends only on the blocks along the
+    // candidate's path, so only paths through a block that may lose an edge
+    // or take part in a merge are watched, along with candidates between two
+    // components a new edge may join. Blocks only merge along the paths
+    // between new edges' endpoints, and erasing edges never puts a block on
+    // a path it was not on, so the paths from the first such endpoint in
+    // each component to the others cover every block the update touches.
+    std::vector<std::pair<std::size_t, bool>> PlanarSubgraph::Impl::watch(const std::vector<std::size_t> &changed,
+                                                                          const std::vector<std::size_t> &also)
+    {
+        refresh();
+        std::unordered_set<std::size_t> touched, touched_comps;
+        std::unordered_map<std::size_t, std::size_t> hubs;
+        auto touch = [&](std::size_t b)
+        {
+            touched.insert(b);
+            touched_comps.insert(forest.component(forest.block(b).verts.front()));
+        };
+        for (size_t i : changed)
         {
-            ogdf::node last_c = nullptr;
-            ogdf::node cur = start;
+            const auto [u, v] = pair_key(edges_all()[i]);
+            if (u == v)
+            {
+                continue;
+            }
+            if (pair_slots.count({u, v}))
+            {
+                touch(forest.common_block(u, v));
+                continue;
+            }
+            for (size_t x : {u, v})
+            {
+                auto [hub, fresh] = hubs.try_emplace(forest.component(x), x);
+                if (!fresh)
+                {
+                    for (const BlockForest::Step &step : forest.path(hub->second, x))
+                    {
+                        touch(step.block);
+                    }
+                }
+            }
+        }
 
-            if (bc.typeOfBNode(cur) != ogdf::BCTree::BNodeType::BComp)
+        std::vector<std::size_t> extra(changed);
+        extra.insert(extra.end(), also.begin(), also.end());
+        std::sort(extra.begin(), extra.end());
+        extra.erase(std::unique(extra.begin(), extra.end()), extra.end());
+        auto crosses = [&](std::size_t u, std::size_t v)
+        {
+            const std::size_t c = forest.component(u);
+            if (c != forest.component(v))
+            {
+                return hubs.count(c) && hubs.count(forest.component(v));
+            }
+            if (!touched_comps.count(c))
             {
-                last_c = cur;
-                cur = bc.parent(cur);
+                return false;
             }
-
-            while (cur && cur != lca)
+            for (const BlockForest::Step &step : forest.path(u, v))
             {
-                ogdf::node pC = (last_c == lca) ? last_c : bc.parent(cur);
-
-                ogdf::node attach_left = bc.cutVertex(pC, cur);
-                ogdf::node attach_right =
-                    (!last_c || last_c == lca) ? bc.repVertex(end_g, cur) : bc.cutVertex(last_c, cur);
-                if (is_left)
-                {
-                    std::swap(attach_left, attach_right);
-                }
-
-                if (!get_or_build_block(bc, cur).block_linkable(attach_left, attach_right))
-                {
-                    return {false, last_c ? last_c : pC};
-                }
-
-                if (pC == lca)
+                if (touched.count(step.block))
                 {
-                    return {true, pC};
+                    return true;
                 }
-                last_c = pC;
-                cur = bc.parent(pC);
             }
-            return {true, last_c};
+            return false;
         };
 
-        auto [ok_l, left_c] = process_branch(uB, w, uG, true);
-        if (!ok_l)
+        std::vector<std::pair<std::size_t, bool>> out;
+        if (touched.empty() && hubs.size() < 2)
         {
-            return false;
+            for (size_t j : extra)
+            {
+                if (!removed[j])
+                {
+                    out.emplace_back(j, resolve(j));
+                }
+            }
+            return out;
         }
-        auto [ok_r, right_c] = process_branch(vB, w, vG, false);
-        if (!ok_r)
+        for (size_t j = 0; j < edges_all().size(); ++j)
         {
-            return false;
+            auto [u, v] = edges_all()[j];
+            if (!removed[j] && (std::binary_search(extra.begin(), extra.end(), j) || crosses(u, v)))
+            {
+                out.emplace_back(j, resolve(j));
+            }
         }
+        return out;
+    }
 
-        if (bc.typeOfBNode(w) == ogdf::BCTree::BNodeType::BComp)
+    std::vector<std::size_t> PlanarSubgraph::Impl::flipped_since(const std::vector<std::pair<std::size_t, bool>> &watched)
+    {
+        std::vector<std::size_t> out;
+        for (auto [j, was] : watched)
         {
-            ogdf::node lattach = (uB == w) ? bc.repVertex(uG, w) : (left_c ? bc.cutVertex(left_c, w) : nullptr);
-            ogdf::node rattach = (vB == w) ? bc.repVertex(vG, w) : (right_c ? bc.cutVertex(right_c, w) : nullptr);
-            if (!get_or_build_block(bc, w).block_linkable(lattach, rattach))
+            if (resolve(j) != was)
             {
-                return false;
+                out.push_back(j);
             }
         }
-        return true;
+        return out;
     }
 
-    PlanarSubgraph::Impl::Impl(const std::size_t num_verts,
-                               const std::vector<Edge> &edges_all_in,
-                               const std::vector<std::uint8_t> &edges_added_init)
-        : num_verts(num_verts),
-          edges_all(edges_all_in),
-          edges_added(edges_all_in.size(), 0u)
+    // Brings the simple graph in line with `edges_added` for the candidates
+    // in `changed`. Nothing is evaluated here; the next query pays for
+    // `refresh`. Before `forest` exists there is nothing to update, as
+    // `ensure_graph` will build it from `edges_added` anyway, but inherited
+    // answers no longer hold.
+    void PlanarSubgraph::Impl::apply(const std::vector<std::size_t> &changed)
     {
-
-        if (edges_added_init.size() != edges_all_in.size())
+        if (changed.empty())
         {
-            throw std::invalid_argument("edges_added must match edges_all length");
+            return;
         }
-
-        for (const auto &[u, v] : edges_all)
+        addable_snap.reset();
+        if (!materialized)
         {
-            if (u >= num_verts || v >= num_verts)
+            if (inherited)
             {
-                throw std::out_of_range("edge endpoint index out of range");
+                std::fill(addable.begin(), addable.end(), UNKNOWN);
+                inherited = false;
             }
-            if (u == v)
+            return;
+        }
+        for (size_t i : changed)
+        {
+            if (edges_added[i] ? link(i) : unlink(i))
             {
-                throw std::invalid_argument("self edge not allowed");
+                pending.try_emplace(pair_key(edges_all()[i]), !edges_added[i]);
+                stale = true;
             }
         }
+    }
 
-        for (size_t i = 0; i < edges_added_init.size(); ++i)
+    // Brings `forest` up to date and starts a new epoch. Only the blocks
+    // holding a lost edge, or on the path of a new one, change; they drop
+    // their SPQR trees and count as changed in this epoch. No answer is
+    // touched: `resolve` checks each against the blocks on its path when
+    // asked.
+    void PlanarSubgraph::Impl::refresh()
+    {
+        ensure_graph();
+        if (!stale)
         {
-            edges_added[i] = edges_added_init[i] ? 1u : 0u;
+            return;
         }
-
-        v_g.resize(static_cast<size_t>(num_verts), nullptr);
-        for (size_t i = 0; i < num_verts; ++i)
+        stale = false;
+        ++epoch;
+        std::vector<std::pair<std::size_t, std::size_t>> lost, gained;
+        for (auto [key, had] : pending)
         {
-            v_g[static_cast<size_t>(i)] = G.newNode();
+            if (had != (pair_slots.count(key) != 0))
+            {
+                (had ? lost : gained).push_back(key);
+            }
         }
-        cur_edges.resize(edges_all.size(), nullptr);
-
-        std::lock_guard<std::mutex> lock(mu);
-        recalculate_addable(edges_added);
-    }
+        pending.clear();
 
-    PlanarSubgraph::Impl::~Impl() = default;
-
-    void PlanarSubgraph::Impl::set(const std::size_t edge_id, const bool present)
-    {
-        std::lock_guard<std::mutex> lock(mu);
-        size_t i = static_cast<size_t>(edge_id);
-        if (i >= edges_added.size())
+        BlockForest::Change change;
+        if (!lost.empty())
         {
-            throw std::out_of_range("edge_id out of range");
+            forest.erase(lost, change);
         }
-
-        if (present == !!edges_added[i])
+        for (auto [u, v] : gained)
         {
-            return;
+            forest.insert(u, v, change);
         }
-
-        edges_added[i] = present ? 1u : 0u;
-        recalculate_addable(edges_added);
-    }
-
-    std::vector<std::uint8_t> PlanarSubgraph::Impl::query() const
-    {
-        std::shared_ptr<const std::vector<std::uint8_t>> snap;
+        block_infos.resize(forest.block_ids());
+        block_since.resize(forest.block_ids(), 0);
+        for (size_t b : change.retired)
         {
-            std::lock_guard<std::mutex> lock(mu);
-            snap = addable_snap;
+            block_infos[b].reset();
         }
-        std::vector<std::uint8_t> out;
-        if (!snap)
+        for (size_t b : change.changed)
         {
-            return out;
+            block_infos[b].reset();
+            block_since[b] = epoch;
         }
-        out.insert(out.end(), snap->begin(), snap->end());
-        return out;
     }
 
-    void PlanarSubgraph::Impl::recalculate_addable(const std::vector<std::uint8_t> &added_snapshot)
+    // The cheap cases are settled from scratch every time. Any other answer
+    // is reused while the blocks on its path are unchanged since it was last
+    // known to hold.
+    bool PlanarSubgraph::Impl::resolve(const std::size_t i)
     {
-        for (size_t i = 0; i < edges_all.size(); ++i)
+        refresh();
+        if (removed[i] || applied()[i] || states[i] == FORBIDDEN)
         {
-            const bool want = added_snapshot[i] != 0;
-            auto [u, v] = edges_all[i];
-            const bool is_loop = (u == v);
-            ogdf::edge &eh = cur_edges[i];
-            if (eh && (!want || is_loop))
-            {
-                G.delEdge(eh);
-                eh = nullptr;
-            }
-            else if (!eh && want && !is_loop)
-            {
-                eh = G.newEdge(v_g[static_cast<size_t>(u)], v_g[static_cast<size_t>(v)]);
-            }
+            return false;
         }
-
-        if (!G.numberOfNodes())
+        auto [u, v] = edges_all()[i];
+        if (u == v || forest.component(u) != forest.component(v) || pair_slots.count(pair_key(edges_all()[i])))
         {
-            auto empty = std::make_shared<std::vector<std::uint8_t>>(edges_all.size(), 0u);
-            std::shared_ptr<const std::vector<std::uint8_t>> empty_const = empty;
-            addable_snap = empty_const;
-            return;
+            return true;
         }
-
-        ogdf::NodeArray<int> comp(G, -1);
-        ogdf::connectedComponents(G, comp);
-
-        BCTreeX bc(G, true);
-        const ogdf::Graph &bc_tree = bc.bcTree();
-
-        reset_blocks(bc_tree);
-
-        auto next_addable = std::make_shared<std::vector<std::uint8_t>>(edges_all.size(), 0u);
-        for (size_t i = 0; i < edges_all.size(); ++i)
+        if (addable[i] == UNKNOWN || (checked_at[i] != epoch && !path_settled(u, v, checked_at[i])))
         {
-            auto [ui, vi] = edges_all[i];
-            ogdf::node u = v_g[static_cast<size_t>(ui)], v = v_g[static_cast<size_t>(vi)];
-
-            if (added_snapshot[i])
-            {
-                (*next_addable)[i] = 0;
-                continue;
-            }
-            if (ui == vi || comp[u] != comp[v])
-            {
-                (*next_addable)[i] = 1;
-                continue;
-            }
-
-            (*next_addable)[i] = can_add_along_bc(bc, u, v);
+            ++evaluated;
+            addable[i] = can_add_along_bc(u, v) ? 1u : 0u;
         }
-
-        std::shared_ptr<const std::vector<std::uint8_t>> pub = next_addable;
-        addable_snap = pub;
+        checked_at[i] = epoch;
+        return addable[i] != 0;
     }
 
     PlanarSubgraph::PlanarSubgraph(const std::size_t num_verts,
@@ -1041,18 +3428,322 @@ namespace graph
                                    const std::vector<std::uint8_t> &edges_added_init)
         : impl(std::make_unique<Impl>(num_verts, edges_all_in, edges_added_init)) {}
 
+    PlanarSubgraph::PlanarSubgraph(std::unique_ptr<Impl> impl) : impl(std::move(impl)) {}
+
     PlanarSubgraph::PlanarSubgraph(PlanarSubgraph &&) noexcept = default;
     PlanarSubgraph &PlanarSubgraph::operator=(PlanarSubgraph &&) noexcept = default;
     PlanarSubgraph::~PlanarSubgraph() = default;
 
+    std::unique_ptr<PlanarSubgraph> PlanarSubgraph::fork() const
+    {
+        return std::unique_ptr<PlanarSubgraph>(new PlanarSubgraph(impl->fork()));
+    }
+
+    std::unique_ptr<PlanarSubgraphReader> PlanarSubgraph::reader() const
+    {
+        return std::unique_ptr<PlanarSubgraphReader>(new PlanarSubgraphReader(impl));
+    }
+
+    std::unique_ptr<AddableSnapshot> PlanarSubgraph::snapshot() const
+    {
+        std::size_t len = 0;
+        auto words = impl->snapshot(len);
+        return std::unique_ptr<AddableSnapshot>(new AddableSnapshot(std::move(words), len));
+    }
+
+    std::size_t PlanarSubgraph::num_verts() const
+    {
+        return impl->vertex_count();
+    }
+
+    bool PlanarSubgraph::is_vertex(const std::size_t v) const
+    {
+        return impl->is_vertex(v);
+    }
+
+    std::size_t PlanarSubgraph::num_edges() const
+    {
+        return impl->num_edges();
+    }
+
+    std::vector<Edge> PlanarSubgraph::edges() const
+    {
+        return impl->edges();
+    }
+
+    Edge PlanarSubgraph::edge(const std::size_t edge_id) const
+    {
+        return impl->edge(edge_id);
+    }
+
+    bool PlanarSubgraph::embedding(std::vector<std::size_t> &rotation_offsets,
+                                   std::vector<std::size_t> &rotation) const
+    {
+        return impl->embedding(rotation_offsets, rotation);
+    }
+
+    bool PlanarSubgraph::witness_embedding(const std::size_t edge_id,
+                                           std::vector<std::size_t> &rotation_offsets,
+                                           std::vector<std::size_t> &rotation) const
+    {
+        return impl->witness_embedding(edge_id, rotation_offsets, rotation);
+    }
+
+    bool PlanarSubgraph::can_attach(const std::vector<std::size_t> &verts) const
+    {
+        return impl->can_attach(verts);
+    }
+
+    bool PlanarSubgraph::attach_embedding(const std::vector<std::size_t> &verts,
+                                          std::vector<std::size_t> &rotation_offsets,
+                                          std::vector<std::size_t> &rotation) const
+    {
+        return impl->attach_embedding(verts, rotation_offsets, rotation);
+    }
+
+    bool PlanarSubgraph::is_candidate(const std::size_t edge_id) const
+    {
+        return impl->is_candidate(edge_id);
+    }
+
+    bool PlanarSubgraph::is_selected(const std::size_t edge_id) const
+    {
+        return impl->is_selected(edge_id);
+    }
+
+    std::uint8_t PlanarSubgraph::state(const std::size_t edge_id) const
+    {
+        return impl->state(edge_id);
+    }
+
+    std::size_t PlanarSubgraph::num_required() const
+    {
+        return impl->num_required();
+    }
+
+    std::uint8_t PlanarSubgraph::check_set(const std::size_t edge_id, const bool present) const
+    {
+        return impl->check_set(edge_id, present);
+    }
+
+    std::uint8_t PlanarSubgraph::check_commit() const
+    {
+        return impl->check_commit();
+    }
+
+    std::size_t PlanarSubgraph::evaluations() const
+    {
+        return impl->evaluations();
+    }
+
+    bool PlanarSubgraph::set_state(const std::size_t edge_id, const std::uint8_t state)
+    {
+        return impl->set_state(edge_id, state);
+    }
+
+    bool PlanarSubgraph::set_state_and_diff(const std::size_t edge_id, const std::uint8_t state,
+                                            std::vector<std::size_t> &flipped)
+    {
+        flipped.clear();
+        return impl->set_state(edge_id, state, &flipped);
+    }
+
+    bool PlanarSubgraph::is_planar() const
+    {
+        return impl->is_planar();
+    }
+
+    bool PlanarSubgraph::in_transaction() const
+    {
+        return impl->in_transaction();
+    }
+
+    std::size_t PlanarSubgraph::add_candidate(const Edge &e)
+    {
+        return impl->add_candidate(e);
+    }
+
+    void PlanarSubgraph::remove_candidate(const std::size_t edge_id)
+    {
+        impl->remove_candidate(edge_id);
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::remove_candidate_and_diff(const std::size_t edge_id)
+    {
+        std::vector<std::size_t> flipped;
+        impl->remove_candidate(edge_id, &flipped);
+        return flipped;
+    }
+
+    std::uint8_t PlanarSubgraph::contract(const std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                                          std::vector<std::size_t> &candidate_map)
+    {
+        vertex_map.clear();
+        candidate_map.clear();
+        return impl->contract(edge_id, vertex_map, candidate_map);
+    }
+
+    std::uint8_t PlanarSubgraph::contract_and_diff(const std::size_t edge_id, std::vector<std::size_t> &vertex_map,
+                                                   std::vector<std::size_t> &candidate_map,
+                                                   std::vector<std::size_t> &flipped)
+    {
+        vertex_map.clear();
+        candidate_map.clear();
+        flipped.clear();
+        return impl->contract(edge_id, vertex_map, candidate_map, &flipped);
+    }
+
+    std::size_t PlanarSubgraph::add_vertex()
+    {
+        return impl->add_vertex();
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::remove_vertex(const std::size_t v)
+    {
+        return impl->remove_vertex(v);
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::remove_vertex_and_diff(const std::size_t v,
+                                                                    std::vector<std::size_t> &flipped)
+    {
+        return impl->remove_vertex(v, &flipped);
+    }
+
     void PlanarSubgraph::set(const std::size_t edge_id, const bool present)
     {
         impl->set(edge_id, present);
     }
 
+    std::vector<std::size_t> PlanarSubgraph::set_and_diff(const std::size_t edge_id, const bool present)
+    {
+        std::vector<std::size_t> flipped;
+        impl->set(edge_id, present, &flipped);
+        return flipped;
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::fill_greedy(const std::vector<std::size_t> &order)
+    {
+        return impl->fill_greedy(order);
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::fill_greedy_and_diff(const std::vector<std::size_t> &order,
+                                                                  std::vector<std::size_t> &flipped)
+    {
+        flipped.clear();
+        return impl->fill_greedy(order, &flipped);
+    }
+
+    bool PlanarSubgraph::commit_and_diff(std::vector<std::size_t> &flipped)
+    {
+        flipped.clear();
+        return impl->commit(&flipped);
+    }
+
+    bool PlanarSubgraph::can_add_all(const std::vector<std::size_t> &edge_ids,
+                                     std::vector<std::size_t> &conflict) const
+    {
+        return impl->can_add_all(edge_ids, conflict);
+    }
+
     std::vector<std::uint8_t> PlanarSubgraph::query() const
     {
         return impl->query();
     }
 
+    bool PlanarSubgraph::is_addable(const std::size_t edge_id) const
+    {
+        return impl->is_addable(edge_id);
+    }
+
+    std::vector<std::uint8_t> PlanarSubgraph::addable_among(const std::vector<std::size_t> &edge_ids) const
+    {
+        return impl->addable_among(edge_ids);
+    }
+
+    bool PlanarSubgraph::can_add(const std::size_t u, const std::size_t v) const
+    {
+        return impl->can_add(u, v);
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::addable_partners(const std::size_t v) const
+    {
+        return impl->addable_partners(v);
+    }
+
+    std::vector<std::size_t> PlanarSubgraph::conflicts(const std::size_t edge_id) const
+    {
+        return impl->conflicts(edge_id);
+    }
+
+    void PlanarSubgraph::conflict_graph(std::vector<std::size_t> &offsets, std::vector<std::size_t> &neighbours) const
+    {
+        impl->conflict_graph(offsets, neighbours);
+    }
+
+    bool PlanarSubgraph::obstruction(const std::size_t edge_id, std::vector<std::size_t> &witness_ids) const
+    {
+        return impl->obstruction(edge_id, witness_ids);
+    }
+
+    bool PlanarSubgraph::begin()
+    {
+        return impl->begin();
+    }
+
+    bool PlanarSubgraph::commit()
+    {
+        return impl->commit();
+    }
+
+    bool PlanarSubgraph::rollback()
+    {
+        return impl->rollback();
+    }
+
+    PlanarSubgraphReader::PlanarSubgraphReader(std::shared_ptr<PlanarSubgraph::Impl> impl) : impl(std::move(impl)) {}
+
+    PlanarSubgraphReader::~PlanarSubgraphReader() = default;
+
+    std::size_t PlanarSubgraphReader::num_edges() const
+    {
+        return impl->num_edges();
+    }
+
+    bool PlanarSubgraphReader::is_candidate(const std::size_t edge_id) const
+    {
+        return impl->is_candidate(edge_id);
+    }
+
+    std::vector<std::uint8_t> PlanarSubgraphReader::query() const
+    {
+        return impl->query();
+    }
+
+    std::unique_ptr<AddableSnapshot> PlanarSubgraphReader::snapshot() const
+    {
+        std::size_t len = 0;
+        auto words = impl->snapshot(len);
+        return std::unique_ptr<AddableSnapshot>(new AddableSnapshot(std::move(words), len));
+    }
+
+    bool PlanarSubgraphReader::is_addable(const std::size_t edge_id) const
+    {
+        return impl->is_addable(edge_id);
+    }
+
+    AddableSnapshot::AddableSnapshot(std::shared_ptr<const std::vector<std::uint64_t>> words, const std::size_t len)
+        : words_(std::move(words)), len_(len) {}
+
+    AddableSnapshot::~AddableSnapshot() = default;
+
+    std::size_t AddableSnapshot::len() const
+    {
+        return len_;
+    }
+
+    const std::vector<std::uint64_t> &AddableSnapshot::words() const
+    {
+        return *words_;
+    }
+
 } // namespace graph
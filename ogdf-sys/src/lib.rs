@@ -23,7 +23,12 @@ pub mod autogen {
         extern_cpp_type!("graph::Edge", crate::pod::Edge)
 
         generate!("graph::boyer_myrvold_witness")
+        generate!("graph::boyer_myrvold_witness_ids")
+        generate!("graph::kuratowski_witnesses")
+        generate!("graph::planar_embedding")
+        generate!("graph::AddableSnapshot")
         generate!("graph::PlanarSubgraph")
+        generate!("graph::PlanarSubgraphReader")
     }
 
     pub use ffi::graph;